@@ -0,0 +1,34 @@
+//! Decoders of forms and proofs must reject malformed input without panicking,
+//! and accept only the one canonical encoding of each value.
+
+#![no_main]
+
+use std::sync::LazyLock;
+
+use libfuzzer_sys::fuzz_target;
+use num_bigint::BigInt;
+use wesolowski_vdf::crypto::generate_discriminant;
+use wesolowski_vdf::{ClassGroupElement, WesolowskiProof};
+
+static DISCRIMINANT: LazyLock<BigInt> = LazyLock::new(|| generate_discriminant(b"fuzz", 512));
+
+fuzz_target!(|data: &[u8]| {
+    let discriminant = &*DISCRIMINANT;
+    
+    if let Ok(form) = ClassGroupElement::try_deserialize(data, discriminant) {
+        assert!(form.is_valid() && form.is_reduced());
+        assert_eq!(form.serialize(), data);
+    }
+    if let Ok(form) = ClassGroupElement::from_canonical_bytes(data, discriminant) {
+        assert!(form.is_valid() && form.is_reduced());
+        assert_eq!(form.to_canonical_bytes(), data);
+    }
+    if let Ok(form) = ClassGroupElement::from_compressed_bytes(data, discriminant) {
+        assert!(form.is_valid() && form.is_reduced());
+        assert_eq!(form.to_compressed_bytes(), data);
+    }
+    if let Ok(proof) = WesolowskiProof::from_bytes(data, discriminant) {
+        assert!(proof.pi.is_valid() && proof.pi.is_reduced());
+        assert_eq!(proof.to_bytes(), data);
+    }
+});
@@ -0,0 +1,40 @@
+//! Verification of attacker-supplied outputs and proofs must return an error
+//! instead of panicking.
+//!
+//! Input layout: u16 iteration count, u32 length and `serialize` encoding of the
+//! output, then the proof bytes. All integers are big-endian.
+
+#![no_main]
+
+use std::sync::LazyLock;
+
+use libfuzzer_sys::fuzz_target;
+use wesolowski_vdf::{ClassGroupElement, VdfParams, WesolowskiProof, WesolowskiVDF};
+
+static VDF: LazyLock<WesolowskiVDF> = LazyLock::new(|| {
+    VdfParams::builder()
+        .discriminant_bits(512)
+        .build_vdf(b"fuzz")
+        .expect("valid parameters")
+});
+
+fuzz_target!(|data: &[u8]| {
+    let vdf = &*VDF;
+    let Some((header, rest)) = data.split_first_chunk::<6>() else {
+        return;
+    };
+    let iterations = u16::from_be_bytes([header[0], header[1]]) as u64;
+    let output_len = u32::from_be_bytes([header[2], header[3], header[4], header[5]]) as usize;
+    if output_len > rest.len() {
+        return;
+    }
+    let (output, proof) = rest.split_at(output_len);
+    
+    let Some(output) = ClassGroupElement::deserialize(output, &vdf.discriminant) else {
+        return;
+    };
+    let Ok(proof) = WesolowskiProof::from_bytes(proof, &vdf.discriminant) else {
+        return;
+    };
+    let _ = vdf.try_verify(&output, &proof, iterations);
+});
@@ -0,0 +1,563 @@
+//! Checkpoint Files for Resumable VDF Evaluation
+//!
+//! This module persists the state of an `Evaluator` so that a long sequential
+//! computation can be resumed after the process is stopped. The saved state holds
+//! everything needed to finish the evaluation and produce the same output and proof
+//! as an uninterrupted run: the iteration counter, the current form, the proof
+//! parameters and the proof checkpoints recorded so far.
+//!
+//! # Files
+//!
+//! A checkpoint consists of two files. The state file at the given path holds the
+//! small, changing part of the state and is replaced on every save. The proof
+//! checkpoints only ever grow, so they are appended to a log file next to it, the
+//! path with `.log` appended. A save therefore writes the checkpoints recorded
+//! since the previous save, not all of them, and its cost does not grow with the
+//! length of the evaluation.
+//!
+//! # State File Format (version 2)
+//!
+//! All integers are big-endian.
+//!
+//! | Field            | Encoding                                              |
+//! |------------------|-------------------------------------------------------|
+//! | magic            | the 8 bytes `WVDFCKPT`                                |
+//! | version          | u8, currently 2                                       |
+//! | discriminant     | u32 length + magnitude bytes (the sign is always −)   |
+//! | generator        | u32 length + `ClassGroupElement::serialize` bytes     |
+//! | iterations       | u64, total number of squarings                        |
+//! | index            | u64, squarings performed so far                       |
+//! | k, l             | u32, u32, proof parameters                            |
+//! | current          | u32 length + `ClassGroupElement::serialize` bytes     |
+//! | checkpoint count | u64                                                   |
+//! | log length       | u64, bytes of the log holding these checkpoints       |
+//! | log digest       | 32 bytes, chained digest of the log records           |
+//! | checksum         | SHA-256 of all preceding bytes                        |
+//!
+//! # Log File Format
+//!
+//! The log is a sequence of records, one per checkpoint, each a u32 length and
+//! `ClassGroupElement::serialize` bytes. Its digest starts as 32 zero bytes and
+//! is replaced by SHA-256(digest ‖ record) for every record, so it can be
+//! extended on each save without reading the log back. Bytes after the log
+//! length recorded in the state file are ignored.
+//!
+//! # Durability
+//!
+//! New records are appended to the log and synced before the state file is
+//! replaced. The state file is written to a temporary sibling, synced and then
+//! renamed over the target, so a crash during a save leaves the previous state
+//! intact, and the log still holds every checkpoint that state refers to.
+
+use num_bigint::{BigInt, Sign};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::class_group::ClassGroupElement;
+use crate::prover::{Evaluator, ProofParameters, MAX_CHECKPOINTS, MAX_DIGIT_BITS};
+
+/// Magic bytes at the start of every checkpoint state file
+pub const CHECKPOINT_MAGIC: &[u8; 8] = b"WVDFCKPT";
+
+/// Current version of the checkpoint file format
+pub const CHECKPOINT_VERSION: u8 = 2;
+/// Errors raised while saving or loading a checkpoint
+#[derive(Debug)]
+pub enum CheckpointError {
+    /// Reading or writing the file failed
+    Io(io::Error),
+    /// The file does not start with `CHECKPOINT_MAGIC`
+    BadMagic,
+    /// The file was written by an unknown format version
+    UnsupportedVersion(u8),
+    /// The trailing SHA-256 checksum does not match the contents
+    ChecksumMismatch,
+    /// The contents are truncated or inconsistent
+    Malformed(&'static str),
+    /// The checkpoint belongs to a different discriminant or generator
+    GeneratorMismatch,
+    /// The evaluation state holds a form that a checkpoint file cannot store
+    InvalidState(&'static str),
+    /// A new checkpoint would replace this existing file
+    AlreadyExists(PathBuf),
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckpointError::Io(err) => write!(f, "checkpoint I/O error: {}", err),
+            CheckpointError::BadMagic => write!(f, "not a checkpoint file"),
+            CheckpointError::UnsupportedVersion(version) => {
+                write!(f, "unsupported checkpoint version {}", version)
+            }
+            CheckpointError::ChecksumMismatch => write!(f, "checkpoint checksum mismatch"),
+            CheckpointError::Malformed(reason) => write!(f, "malformed checkpoint: {}", reason),
+            CheckpointError::GeneratorMismatch => {
+                write!(f, "checkpoint was written for a different discriminant or generator")
+            }
+            CheckpointError::InvalidState(reason) => write!(f, "cannot save checkpoint: {}", reason),
+            CheckpointError::AlreadyExists(path) => write!(
+                f,
+                "{} already exists; resume the checkpoint or remove it first",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CheckpointError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CheckpointError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CheckpointError {
+    fn from(err: io::Error) -> Self {
+        CheckpointError::Io(err)
+    }
+}
+
+/// Writer that saves the state of one evaluation to a checkpoint file
+/// 
+/// The writer keeps the checkpoint log open and remembers how many checkpoints
+/// it holds, so each `save` appends only the new ones.
+pub struct CheckpointWriter {
+    path: PathBuf,
+    log: fs::File,
+    log_len: u64,
+    digest: [u8; 32],
+    saved: usize,
+}
+
+impl CheckpointWriter {
+    /// Start a new checkpoint file at `path` for an evaluation
+    /// 
+    /// Writes the log with the checkpoints the evaluator has recorded so far and
+    /// then the state file, so the evaluation can be resumed from its start.
+    /// 
+    /// Returns `CheckpointError::AlreadyExists` if the state file or the log is
+    /// already present; an interrupted evaluation is continued with `resume`, and
+    /// its files are never overwritten by a new one. Returns
+    /// `CheckpointError::InvalidState` if the generator, the current form or a
+    /// checkpoint is not a reduced form, so an evaluation that could not be saved
+    /// fails before it starts.
+    pub fn create(path: &Path, evaluator: &Evaluator) -> Result<Self, CheckpointError> {
+        let discriminant = &evaluator.generator().discriminant;
+        check_form(evaluator.generator(), discriminant)?;
+        check_form(evaluator.current(), discriminant)?;
+        for checkpoint in evaluator.checkpoints() {
+            check_form(checkpoint, discriminant)?;
+        }
+        if path.try_exists()? {
+            return Err(CheckpointError::AlreadyExists(path.to_path_buf()));
+        }
+        
+        let log_path = log_path(path);
+        let log = fs::OpenOptions::new()
+            .append(true)
+            .create_new(true)
+            .open(&log_path)
+            .map_err(|err| match err.kind() {
+                io::ErrorKind::AlreadyExists => CheckpointError::AlreadyExists(log_path.clone()),
+                _ => CheckpointError::Io(err),
+            })?;
+        let mut writer = Self {
+            path: path.to_path_buf(),
+            log,
+            log_len: 0,
+            digest: [0u8; 32],
+            saved: 0,
+        };
+        writer.save(evaluator)?;
+        Ok(writer)
+    }
+
+    /// Load the checkpoint file at `path` and continue writing to it
+    /// 
+    /// Returns the saved evaluation state and a writer that appends to the
+    /// existing log.
+    pub fn resume(path: &Path) -> Result<(Evaluator, Self), CheckpointError> {
+        let saved = read(path)?;
+        let log = fs::OpenOptions::new().append(true).open(log_path(path))?;
+        // Drop records appended by a save that did not complete
+        log.set_len(saved.log_len)?;
+        
+        let writer = Self {
+            path: path.to_path_buf(),
+            log,
+            log_len: saved.log_len,
+            digest: saved.digest,
+            saved: saved.evaluator.checkpoints().len(),
+        };
+        Ok((saved.evaluator, writer))
+    }
+
+    /// Save the evaluation state
+    /// 
+    /// Appends the checkpoints recorded since the previous save to the log, then
+    /// atomically replaces the state file.
+    /// 
+    /// Only states that `load` can read back are written: the generator, the current
+    /// form and the checkpoints must be reduced forms of one discriminant, otherwise
+    /// `CheckpointError::InvalidState` is returned and nothing is written.
+    pub fn save(&mut self, evaluator: &Evaluator) -> Result<(), CheckpointError> {
+        let checkpoints = evaluator.checkpoints();
+        if checkpoints.len() < self.saved {
+            return Err(CheckpointError::Malformed("evaluation has fewer checkpoints than already saved"));
+        }
+        let discriminant = &evaluator.generator().discriminant;
+        check_form(evaluator.generator(), discriminant)?;
+        check_form(evaluator.current(), discriminant)?;
+        for checkpoint in &checkpoints[self.saved..] {
+            check_form(checkpoint, discriminant)?;
+        }
+        
+        let mut records = Vec::new();
+        for checkpoint in &checkpoints[self.saved..] {
+            let record = record(checkpoint);
+            self.digest = chain(&self.digest, &record);
+            records.extend_from_slice(&record);
+        }
+        if !records.is_empty() {
+            self.log.write_all(&records)?;
+            self.log.sync_data()?;
+            self.log_len += records.len() as u64;
+            self.saved = checkpoints.len();
+        }
+        
+        let bytes = encode_state(evaluator, self.log_len, &self.digest);
+        let temporary = temporary_path(&self.path);
+        let mut file = fs::File::create(&temporary)?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&temporary, &self.path)?;
+        Ok(())
+    }
+}
+
+/// Path of the checkpoint log that belongs to the state file at `path`
+pub fn log_path(path: &Path) -> PathBuf {
+    let mut log = path.as_os_str().to_owned();
+    log.push(".log");
+    PathBuf::from(log)
+}
+
+/// Write a new checkpoint file, state and log
+/// 
+/// Like `CheckpointWriter::create`, this refuses to replace existing files. For
+/// repeated saves of one evaluation use a `CheckpointWriter`, which only appends
+/// new checkpoints.
+pub fn save(path: &Path, evaluator: &Evaluator) -> Result<(), CheckpointError> {
+    CheckpointWriter::create(path, evaluator).map(drop)
+}
+
+/// Read a checkpoint file and its log
+pub fn load(path: &Path) -> Result<Evaluator, CheckpointError> {
+    Ok(read(path)?.evaluator)
+}
+
+/// Contents of a checkpoint file with the position of its log
+struct Saved {
+    evaluator: Evaluator,
+    log_len: u64,
+    digest: [u8; 32],
+}
+
+fn read(path: &Path) -> Result<Saved, CheckpointError> {
+    let state = fs::read(path)?;
+    let log = fs::read(log_path(path))?;
+    decode(&state, &log)
+}
+
+/// Encode the state file contents
+fn encode_state(evaluator: &Evaluator, log_len: u64, digest: &[u8; 32]) -> Vec<u8> {
+    let generator = evaluator.generator();
+    let mut out = Vec::new();
+    out.extend_from_slice(CHECKPOINT_MAGIC);
+    out.push(CHECKPOINT_VERSION);
+    
+    let (_, discriminant_bytes) = generator.discriminant.to_bytes_be();
+    put_bytes(&mut out, &discriminant_bytes);
+    put_bytes(&mut out, &generator.serialize());
+    
+    let parameters = evaluator.parameters();
+    out.extend_from_slice(&evaluator.iterations().to_be_bytes());
+    out.extend_from_slice(&evaluator.iterations_done().to_be_bytes());
+    out.extend_from_slice(&parameters.k.to_be_bytes());
+    out.extend_from_slice(&parameters.l.to_be_bytes());
+    put_bytes(&mut out, &evaluator.current().serialize());
+    
+    out.extend_from_slice(&(evaluator.checkpoints().len() as u64).to_be_bytes());
+    out.extend_from_slice(&log_len.to_be_bytes());
+    out.extend_from_slice(digest);
+    
+    let checksum = Sha256::digest(&out);
+    out.extend_from_slice(&checksum);
+    out
+}
+
+/// Decode a state file and the log it refers to
+fn decode(bytes: &[u8], log: &[u8]) -> Result<Saved, CheckpointError> {
+    if bytes.len() < CHECKPOINT_MAGIC.len() + 1 + 32 {
+        return Err(CheckpointError::Malformed("file is too short"));
+    }
+    if &bytes[..CHECKPOINT_MAGIC.len()] != CHECKPOINT_MAGIC {
+        return Err(CheckpointError::BadMagic);
+    }
+    let version = bytes[CHECKPOINT_MAGIC.len()];
+    if version != CHECKPOINT_VERSION {
+        return Err(CheckpointError::UnsupportedVersion(version));
+    }
+    
+    let (body, checksum) = bytes.split_at(bytes.len() - 32);
+    if Sha256::digest(body).as_slice() != checksum {
+        return Err(CheckpointError::ChecksumMismatch);
+    }
+    
+    let mut reader = Reader { bytes: body, offset: CHECKPOINT_MAGIC.len() + 1 };
+    let discriminant = -BigInt::from_bytes_be(Sign::Plus, reader.bytes()?);
+    let generator = reader.form(&discriminant)?;
+    
+    let iterations = reader.u64()?;
+    let index = reader.u64()?;
+    let k = reader.u32()?;
+    let l = reader.u32()?;
+    if index > iterations {
+        return Err(CheckpointError::Malformed("iteration index exceeds the total"));
+    }
+    if !(1..=MAX_DIGIT_BITS).contains(&k) || l == 0 {
+        return Err(CheckpointError::Malformed("invalid proof parameters"));
+    }
+    let parameters = ProofParameters { k, l };
+    let current = reader.form(&discriminant)?;
+    
+    let count = reader.u64()?;
+    if count != index.div_ceil(parameters.checkpoint_interval()) {
+        return Err(CheckpointError::Malformed("checkpoint count does not match the index"));
+    }
+    let log_len = reader.u64()?;
+    let mut digest = [0u8; 32];
+    digest.copy_from_slice(reader.take(32)?);
+    if reader.offset != body.len() {
+        return Err(CheckpointError::Malformed("trailing bytes after the log digest"));
+    }
+    
+    let log = usize::try_from(log_len)
+        .ok()
+        .and_then(|len| log.get(..len))
+        .ok_or(CheckpointError::Malformed("log is shorter than recorded"))?;
+    let mut reader = Reader { bytes: log, offset: 0 };
+    let mut chained = [0u8; 32];
+    let mut checkpoints = Vec::with_capacity(count.min(MAX_CHECKPOINTS) as usize);
+    for _ in 0..count {
+        let start = reader.offset;
+        checkpoints.push(reader.form(&discriminant)?);
+        chained = chain(&chained, &log[start..reader.offset]);
+    }
+    if reader.offset != log.len() {
+        return Err(CheckpointError::Malformed("log length does not match the checkpoints"));
+    }
+    if chained != digest {
+        return Err(CheckpointError::ChecksumMismatch);
+    }
+    if checkpoints.first().is_some_and(|first| *first != generator) {
+        return Err(CheckpointError::Malformed("first checkpoint is not the generator"));
+    }
+    
+    let evaluator = Evaluator::from_parts(generator, iterations, parameters, index, current, checkpoints);
+    Ok(Saved { evaluator, log_len, digest })
+}
+
+/// Check that `decode` will accept a form of the state
+fn check_form(form: &ClassGroupElement, discriminant: &BigInt) -> Result<(), CheckpointError> {
+    if form.discriminant != *discriminant {
+        return Err(CheckpointError::InvalidState("forms have different discriminants"));
+    }
+    if !form.is_valid() {
+        return Err(CheckpointError::InvalidState("form is not positive definite with its discriminant"));
+    }
+    if !form.is_reduced() {
+        return Err(CheckpointError::InvalidState("form is not reduced"));
+    }
+    Ok(())
+}
+
+/// Log record of one checkpoint
+fn record(checkpoint: &ClassGroupElement) -> Vec<u8> {
+    let mut out = Vec::new();
+    put_bytes(&mut out, &checkpoint.serialize());
+    out
+}
+
+/// Extend the log digest by one record
+fn chain(digest: &[u8; 32], record: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(digest);
+    hasher.update(record);
+    hasher.finalize().into()
+}
+
+fn temporary_path(path: &Path) -> PathBuf {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    PathBuf::from(temporary)
+}
+
+fn put_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    out.extend_from_slice(bytes);
+}
+
+/// Cursor over the checkpoint body
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], CheckpointError> {
+        let end = self.offset.checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or(CheckpointError::Malformed("unexpected end of data"))?;
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn u32(&mut self) -> Result<u32, CheckpointError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn u64(&mut self) -> Result<u64, CheckpointError> {
+        let bytes = self.take(8)?;
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(bytes);
+        Ok(u64::from_be_bytes(buf))
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], CheckpointError> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+
+    fn form(&mut self, discriminant: &BigInt) -> Result<ClassGroupElement, CheckpointError> {
+        let bytes = self.bytes()?;
+        ClassGroupElement::deserialize(bytes, discriminant)
+            .ok_or(CheckpointError::Malformed("invalid form encoding"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discriminant::Discriminant;
+    use crate::vdf::WesolowskiVDF;
+
+    const ITERATIONS: u64 = 300;
+
+    fn instance() -> (WesolowskiVDF, ClassGroupElement) {
+        let discriminant = Discriminant::from_seed(b"checkpoint-test", 512).unwrap();
+        let generator = ClassGroupElement::generator(discriminant.as_bigint().clone());
+        let vdf = WesolowskiVDF::from_discriminant(discriminant, generator.clone()).unwrap();
+        (vdf, generator)
+    }
+
+    /// Empty directory for the files of one test
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("wesolowski-vdf-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn resume_matches_uninterrupted_compute() {
+        let (vdf, generator) = instance();
+        let (expected_output, expected_proof) = vdf.compute(ITERATIONS);
+        
+        let dir = scratch_dir("resume");
+        let path = dir.join("run.ckpt");
+        let mut evaluator = Evaluator::new(&generator, ITERATIONS);
+        let mut writer = CheckpointWriter::create(&path, &evaluator).unwrap();
+        for _ in 0..3 {
+            evaluator.run(50);
+            writer.save(&evaluator).unwrap();
+        }
+        // Work after the last save is lost when the process stops
+        evaluator.run(20);
+        drop(writer);
+        
+        let (output, proof) = vdf.resume(&path, 40).unwrap();
+        assert_eq!(output, expected_output);
+        assert_eq!(proof.to_bytes(), expected_proof.to_bytes());
+        assert!(vdf.verify(&output, &proof, ITERATIONS));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn create_refuses_existing_files() {
+        let (vdf, generator) = instance();
+        let dir = scratch_dir("exists");
+        let path = dir.join("run.ckpt");
+        let mut evaluator = Evaluator::new(&generator, ITERATIONS);
+        let mut writer = CheckpointWriter::create(&path, &evaluator).unwrap();
+        evaluator.run(100);
+        writer.save(&evaluator).unwrap();
+        drop(writer);
+        
+        let fresh = Evaluator::new(&generator, ITERATIONS);
+        assert!(matches!(
+            CheckpointWriter::create(&path, &fresh),
+            Err(CheckpointError::AlreadyExists(_))
+        ));
+        assert!(matches!(
+            vdf.compute_checkpointed(ITERATIONS, &path, 50),
+            Err(CheckpointError::AlreadyExists(_))
+        ));
+        let saved = load(&path).unwrap();
+        assert_eq!(saved.iterations_done(), 100);
+        
+        // A log without a state file is not replaced either
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            CheckpointWriter::create(&path, &fresh),
+            Err(CheckpointError::AlreadyExists(found)) if found == log_path(&path)
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn load_rejects_corrupted_files() {
+        let (_, generator) = instance();
+        let dir = scratch_dir("corrupt");
+        let path = dir.join("run.ckpt");
+        let mut evaluator = Evaluator::new(&generator, ITERATIONS);
+        evaluator.run(150);
+        save(&path, &evaluator).unwrap();
+        let state = fs::read(&path).unwrap();
+        let log = fs::read(log_path(&path)).unwrap();
+        
+        let mut flipped = state.clone();
+        flipped[20] ^= 1;
+        fs::write(&path, &flipped).unwrap();
+        assert!(matches!(load(&path), Err(CheckpointError::ChecksumMismatch)));
+        
+        fs::write(&path, &state).unwrap();
+        let mut flipped = log.clone();
+        flipped[10] ^= 1;
+        fs::write(log_path(&path), &flipped).unwrap();
+        assert!(matches!(load(&path), Err(CheckpointError::Malformed(_))));
+        
+        fs::write(log_path(&path), &log[..log.len() - 1]).unwrap();
+        assert!(matches!(load(&path), Err(CheckpointError::Malformed(_))));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -16,9 +16,52 @@
 //! - **Sequential Nature**: Computing g^(2^t) requires t sequential squaring operations
 //! - **Parallel Resistance**: No known method to parallelize the computation significantly
 //! - **Verifiable**: Proofs can be verified much faster than generation
+//!
+//! # Encodings
+//!
+//! - `serialize` writes a, b and c with length prefixes. It is not compact, since c
+//!   is implied by a, b and Δ, but `try_deserialize` accepts only minimal lengths
+//!   and reduced forms, so each element has exactly one accepted encoding. Binary
+//!   serde formats use this layout.
+//! - `to_canonical_bytes` writes a reduced form as a sign byte followed by a and |b|,
+//!   each padded to ⌈bits(|Δ|)/16⌉ big-endian bytes.
+//! - `to_compressed_bytes` writes the compressed encoding of Chia's `bqfc`, about
+//!   three quarters of the canonical size and 100 bytes for |Δ| of up to 1024 bits.
+//!
+//! Both fixed-size decoders accept only the encoding of a reduced form of the
+//! given discriminant, so every element has exactly one valid encoding.
 
-use num_bigint::{BigInt, Sign};
+use num_bigint::{BigInt, BigUint, Sign};
+use num_integer::Integer;
 use num_traits::{Zero, One, Signed};
+use std::fmt;
+use std::ops::{Mul, Neg};
+
+use crate::crypto::{hash_to_group, is_probably_prime, kronecker, sqrt_mod_prime};
+use crate::error::VdfError;
+
+/// Reasons for rejecting a triple (a, b, c) as a form of a given discriminant
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FormError {
+    /// The leading coefficient a is not positive, so the form is not positive definite
+    NonPositiveLeadingCoefficient,
+    /// b² - 4ac does not equal the discriminant
+    DiscriminantMismatch,
+    /// The form is valid but not reduced, where a reduced form was required
+    NotReduced,
+}
+
+impl fmt::Display for FormError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormError::NonPositiveLeadingCoefficient => write!(f, "leading coefficient a must be positive"),
+            FormError::DiscriminantMismatch => write!(f, "b² - 4ac does not match the discriminant"),
+            FormError::NotReduced => write!(f, "form is not reduced"),
+        }
+    }
+}
+
+impl std::error::Error for FormError {}
 
 /// Class group element representing a binary quadratic form (a, b, c)
 /// with discriminant D = b² - 4ac
@@ -64,147 +107,445 @@ impl ClassGroupElement {
         Self { a, b, c, discriminant }
     }
 
+    /// Create the reduced prime form of norm p, if one exists
+    /// 
+    /// For a prime p with Kronecker symbol (Δ/p) = 1 there is a form (p, b, c) of
+    /// discriminant Δ: b is the square root of Δ modulo p, or for p = 2 simply 1,
+    /// with the parity of Δ so that 4p divides b² - Δ. The form with 0 < b < p is
+    /// returned reduced; its inverse is the form with -b.
+    /// 
+    /// # Returns
+    /// `None` if p is not a probable prime or (Δ/p) ≠ 1
+    /// 
+    /// # Panics
+    /// Panics if Δ is not negative or not ≡ 0, 1 (mod 4).
+    pub fn prime_form(discriminant: BigInt, p: &BigInt) -> Option<Self> {
+        let residue = discriminant.mod_floor(&BigInt::from(4));
+        assert!(
+            discriminant.is_negative() && residue <= BigInt::one(),
+            "discriminant must be negative and ≡ 0, 1 (mod 4)"
+        );
+        if !is_probably_prime(p) || kronecker(&discriminant, p) != 1 {
+            return None;
+        }
+        
+        let mut b = if p == &BigInt::from(2) {
+            BigInt::one()
+        } else {
+            sqrt_mod_prime(&discriminant, p).expect("Δ is a square mod p")
+        };
+        if b.is_odd() != discriminant.is_odd() {
+            b = p - b;
+        }
+        Some(Self::new(p.clone(), b, discriminant).reduced())
+    }
+
+    /// Map bytes deterministically to a form of discriminant Δ
+    /// 
+    /// The bytes and a counter are hashed to 256-bit primes p until one has a prime
+    /// form; see `crypto::hash_to_group`. Distinct inputs give independent elements
+    /// whose derivation anyone can check.
+    /// 
+    /// Returns `VdfError::InvalidDiscriminant` if Δ is not negative or not ≡ 1 (mod 4).
+    pub fn from_hash(discriminant: BigInt, bytes: &[u8]) -> Result<Self, VdfError> {
+        hash_to_group(&discriminant, bytes)
+    }
+
+    /// Create a class group element from all three coefficients
+    /// 
+    /// Unlike `new`, nothing is recomputed: the triple is rejected unless it is a
+    /// positive definite form of the given discriminant, i.e. a > 0 and b² - 4ac = Δ.
+    /// The form is returned as given, without reduction.
+    pub fn try_new(a: BigInt, b: BigInt, c: BigInt, discriminant: BigInt) -> Result<Self, FormError> {
+        if !a.is_positive() {
+            return Err(FormError::NonPositiveLeadingCoefficient);
+        }
+        if &b * &b - 4 * &a * &c != discriminant {
+            return Err(FormError::DiscriminantMismatch);
+        }
+        Ok(Self { a, b, c, discriminant })
+    }
+
+    /// Check that the form is positive definite with the stored discriminant
+    /// 
+    /// Returns `true` when a > 0 and b² - 4ac = Δ.
+    pub fn is_valid(&self) -> bool {
+        self.a.is_positive() && &self.b * &self.b - 4 * &self.a * &self.c == self.discriminant
+    }
+
+    /// Check whether the form is normalized, i.e. -a < b ≤ a
+    pub fn is_normalized(&self) -> bool {
+        -&self.a < self.b && self.b <= self.a
+    }
+
+    /// Check whether the form is reduced
+    /// 
+    /// A reduced form is normalized, satisfies a ≤ c, and has b ≥ 0 when a = c.
+    pub fn is_reduced(&self) -> bool {
+        self.is_normalized() && self.a <= self.c && !(self.a == self.c && self.b.is_negative())
+    }
+
+    /// Normalize the form so that -a < b ≤ a
+    /// 
+    /// This applies the translation x -> x + ry with r = ⌊(a - b) / 2a⌋, which keeps
+    /// a and the discriminant unchanged. Requires a > 0.
+    pub fn normalize(&mut self) {
+        if self.is_normalized() {
+            return;
+        }
+        let r = (&self.a - &self.b).div_floor(&(2 * &self.a));
+        // c' = a·r² + b·r + c, using the old b
+        self.c = &self.c + &r * (&self.b + &r * &self.a);
+        self.b = &self.b + 2 * &r * &self.a;
+    }
+
     /// Reduce the binary quadratic form to its canonical representation
     /// 
     /// A reduced form satisfies: |b| ≤ a ≤ c and if |b| = a or a = c, then b ≥ 0
-    /// This implements the standard reduction algorithm used in class group cryptography.
+    /// The form is normalized first; then, while a > c, it is replaced by the
+    /// equivalent form (c, -b, a) and normalized again. Every such step strictly
+    /// decreases the positive integer a, so the loop always terminates, and both
+    /// steps are unimodular changes of variables that preserve the discriminant.
+    /// Requires a valid form (see `is_valid`).
     pub fn reduce(&mut self) {
-        let mut steps = 0;
-        const MAX_STEPS: usize = 1000;
-        
-        while steps < MAX_STEPS {
-            // If a > c, swap a and c, and negate b
-            if self.a > self.c {
-                std::mem::swap(&mut self.a, &mut self.c);
-                self.b = -&self.b;
-            }
-            
-            // If |b| > a, reduce b modulo 2a
-            let abs_b = self.b.abs();
-            if abs_b > self.a {
-                let q = (&self.b + &self.a) / (2 * &self.a);
-                let old_b = self.b.clone();
-                self.b = &self.b - 2 * &q * &self.a;
-                self.c = &self.c - &q * (&old_b + &q * &self.a);
-            }
-            
-            // Check if we're done
-            let abs_b = self.b.abs();
-            if abs_b <= self.a && self.a <= self.c {
-                // If |b| = a or a = c, ensure b ≥ 0
-                if (abs_b == self.a || self.a == self.c) && self.b.is_negative() {
-                    self.b = -&self.b;
-                }
-                break;
-            }
-            
-            steps += 1;
+        self.normalize();
+        while self.a > self.c {
+            std::mem::swap(&mut self.a, &mut self.c);
+            self.b = -&self.b;
+            self.normalize();
         }
         
-        // Verify the discriminant is preserved
-        let computed_discriminant = &self.b * &self.b - 4 * &self.a * &self.c;
-        if computed_discriminant != self.discriminant {
-            // Recalculate c to maintain discriminant
-            self.c = (&self.b * &self.b - &self.discriminant) / (4 * &self.a);
+        // (a, -b, a) and (a, b, a) are equivalent; keep b ≥ 0
+        if self.a == self.c && self.b.is_negative() {
+            self.b = -&self.b;
         }
     }
 
     /// Compose two class group elements using the NUCOMP algorithm
     /// 
-    /// This implements composition of binary quadratic forms, which is the
-    /// group operation in the class group. The result is automatically reduced.
+    /// This is Shanks' NUCOMP in the formulation of Jacobson and van der Poorten.
+    /// The Bezout data of the composition is computed from the leading coefficients,
+    /// but the unreduced product form (a1·a2/d², ...) is never built. Instead, a
+    /// partial extended Euclidean reduction of (a1/d, U) is stopped once the remainder
+    /// drops below ≈ √(a1/a2)·|Δ/4|^(1/4), which yields an almost reduced form from
+    /// operands of roughly half the size of Δ. A few `reduce()` steps finish the job.
+    /// 
+    /// # Panics
+    /// Panics if the discriminants differ; see `try_compose`.
     pub fn compose(&self, other: &ClassGroupElement) -> ClassGroupElement {
         // Ensure both elements have the same discriminant
         assert_eq!(self.discriminant, other.discriminant);
         
-        // Handle identity elements
-        if self.a == BigInt::one() {
-            return other.clone();
-        }
-        if other.a == BigInt::one() {
-            return self.clone();
-        }
+        // Order the operands so that a1 ≥ a2
+        let (f1, f2) = if self.a >= other.a { (self, other) } else { (other, self) };
+        let (a1, b1) = (&f1.a, &f1.b);
+        let (a2, b2, c2) = (&f2.a, &f2.b, &f2.c);
         
-        let (a1, b1, _) = (&self.a, &self.b, &self.c);
-        let (a2, b2, _) = (&other.a, &other.b, &other.c);
+        // s = (b1 + b2) / 2 and n = (b2 - b1) / 2 are exact since b1 ≡ b2 ≡ Δ (mod 2)
+        let s: BigInt = (b1 + b2) / 2;
+        let n: BigInt = (b2 - b1) / 2;
         
-        // Compute gcd(a1, a2, (b1 + b2)/2)
-        let s = (b1 + b2) / 2;
-        let g = gcd(&gcd(a1, a2), &s);
+        // First Euclidean step: d = gcd(a1, a2) = y1·a2 + v·a1
+        let (d, y1) = if (a2 % a1).is_zero() {
+            (a1.clone(), BigInt::zero())
+        } else {
+            lehmer_gcd_cofactor(a2, a1)
+        };
         
-        if g == BigInt::from(1) {
-            // Simple case: gcd = 1
-            let a3 = a1 * a2;
-            let b3 = b1 + 2 * a2 * ((b2 - b1) / 2);
-            let c3 = (&b3 * &b3 - &self.discriminant) / (4 * &a3);
-            
-            let mut result = ClassGroupElement {
-                a: a3,
-                b: b3,
-                c: c3,
-                discriminant: self.discriminant.clone(),
-            };
-            result.reduce();
-            result
+        // Second Euclidean step: d1 = gcd(s, d) = x2·s - y2·d
+        let (d1, x2, y2) = if (&s % &d).is_zero() {
+            (d, BigInt::zero(), -BigInt::one())
         } else {
-            // General case: use extended Euclidean algorithm
-            let a1_g = a1 / &g;
-            let a2_g = a2 / &g;
-            let s_g = &s / &g;
-            
-            // Extended GCD to find Bezout coefficients
-            let (_, u, _) = extended_gcd(&a1_g, &a2_g);
-            
-            let a3 = &g * &a1_g * &a2_g;
-            let b3 = b1 + 2 * &g * &a2_g * &u * (&s_g - b1 / &g);
-            let c3 = (&b3 * &b3 - &self.discriminant) / (4 * &a3);
-            
-            let mut result = ClassGroupElement {
-                a: a3,
-                b: b3,
-                c: c3,
-                discriminant: self.discriminant.clone(),
-            };
-            result.reduce();
-            result
+            let (d1, x2) = lehmer_gcd_cofactor(&s, &d);
+            let y2 = (&x2 * &s - &d1) / &d;
+            (d1, x2, y2)
+        };
+        
+        let v1 = a1 / &d1;
+        let v2 = a2 / &d1;
+        let u = (y1 * y2 * n - x2 * c2).mod_floor(&v1);
+        
+        let bound = nucomp_bound(&v1, &v2, &self.discriminant);
+        nucomp_finish(&v1, &v2, &d1, b2, c2, u, &bound, &self.discriminant)
+    }
+
+    /// Compose two class group elements, rejecting mismatched discriminants
+    /// 
+    /// Same as `compose`, but returns `VdfError::DiscriminantMismatch` instead of
+    /// panicking when the operands belong to different class groups.
+    pub fn try_compose(&self, other: &ClassGroupElement) -> Result<ClassGroupElement, VdfError> {
+        if self.discriminant != other.discriminant {
+            return Err(VdfError::DiscriminantMismatch);
         }
+        Ok(self.compose(other))
+    }
+
+    /// Compose two class group elements with the classical (Dirichlet/Shanks) formulas
+    /// 
+    /// This builds the full product form (a1·a2/e², B, C) from a single triple Bezout
+    /// identity e = u·a1 + v·a2 + w·(b1 + b2)/2 and then runs a complete reduction.
+    /// It works on full-sized operands and is much slower than `compose`; it exists as
+    /// an independent reference that `compose` must agree with on reduced output.
+    pub fn compose_reference(&self, other: &ClassGroupElement) -> ClassGroupElement {
+        assert_eq!(self.discriminant, other.discriminant);
+        
+        let (a1, b1) = (&self.a, &self.b);
+        let (a2, b2) = (&other.a, &other.b);
+        let s: BigInt = (b1 + b2) / 2;
+        
+        // e = gcd(a1, a2, s) = u·a1 + v·a2 + w·s
+        let (g, p, q) = extended_gcd(a1, a2);
+        let (e, r, w) = extended_gcd(&g, &s);
+        let u = &r * p;
+        let v = &r * q;
+        
+        let a3 = (a1 * a2) / (&e * &e);
+        let b_num: BigInt = &u * a1 * b2 + &v * a2 * b1 + &w * ((b1 * b2 + &self.discriminant) / 2);
+        let b3 = (b_num / &e).mod_floor(&(2 * &a3));
+        let c3 = (&b3 * &b3 - &self.discriminant) / (4 * &a3);
+        
+        let mut result = ClassGroupElement {
+            a: a3,
+            b: b3,
+            c: c3,
+            discriminant: self.discriminant.clone(),
+        };
+        result.reduce();
+        result
     }
 
-    /// Square the element (self * self) using class group composition
+    /// Square the element (self * self) using the NUDUPL algorithm
     /// 
-    /// This is an optimized version of composition when both operands are the same.
+    /// This is the doubling specialisation of NUCOMP. With both operands equal the
+    /// first Euclidean step is trivial, so a single extended gcd of (b, a) gives all
+    /// the Bezout data, and the partial reduction bound is simply |Δ/4|^(1/4).
+    /// Squaring is the only operation in the sequential VDF loop, so `compute`,
+    /// `benchmark` and `pow` all go through here.
     pub fn square(&self) -> ClassGroupElement {
-        self.compose(self)
+        let (a, b, c) = (&self.a, &self.b, &self.c);
+        
+        // d1 = gcd(a, b) = x·b + y·a, only x is needed
+        let (d1, x) = lehmer_gcd_cofactor(b, a);
+        let v = a / &d1;
+        let u = (-(x * c)).mod_floor(&v);
+        
+        let bound = nucomp_bound(&v, &v, &self.discriminant);
+        nucomp_finish(&v, &v, &d1, b, c, u, &bound, &self.discriminant)
+    }
+
+    /// Compute the inverse class, (a, -b, c) normalized
+    /// 
+    /// Inversion is free in the class group: the opposite form (a, -b, c) is the
+    /// inverse of (a, b, c). For a reduced input the result is reduced as well.
+    pub fn inverse(&self) -> ClassGroupElement {
+        let mut result = ClassGroupElement {
+            a: self.a.clone(),
+            b: -&self.b,
+            c: self.c.clone(),
+            discriminant: self.discriminant.clone(),
+        };
+        result.normalize();
+        // (a, -b, a) and (a, b, a) are the same class
+        if result.a == result.c && result.b.is_negative() {
+            result.b = -&result.b;
+        }
+        result
+    }
+
+    /// Divide by another element, i.e. compose with its inverse
+    pub fn div(&self, other: &ClassGroupElement) -> ClassGroupElement {
+        self.compose(&other.inverse())
+    }
+
+    /// Check whether the element is in the principal class
+    /// 
+    /// The form does not need to be reduced: the check is done on its reduction.
+    pub fn is_identity(&self) -> bool {
+        if self.is_reduced() {
+            self.a.is_one()
+        } else {
+            self.reduced().a.is_one()
+        }
     }
 
-    /// Exponentiation by repeated squaring with proper class group operations
+    /// Check whether two possibly unreduced forms lie in the same class
     /// 
-    /// Computes self^exp using the binary exponentiation algorithm.
-    /// This is the core operation used in VDF computation.
+    /// Reduced forms are unique in their class, so this compares the reductions.
+    pub fn equivalent(&self, other: &ClassGroupElement) -> bool {
+        self.discriminant == other.discriminant && self.reduced() == other.reduced()
+    }
+
+    /// Return the reduced representative of this element's class
+    pub fn reduced(&self) -> ClassGroupElement {
+        let mut result = self.clone();
+        result.reduce();
+        result
+    }
+
+    /// Exponentiation using signed-digit (wNAF) windows
+    /// 
+    /// Computes self^exp for any integer exponent. Since inverting a form is free,
+    /// the exponent is recoded in width-w non-adjacent form, whose digits are odd
+    /// and lie in (-2^(w-1), 2^(w-1)); only the positive odd powers of self are
+    /// precomputed and negative digits use their inverses. Negative exponents are
+    /// handled by inverting the base. All squarings go through NUDUPL.
     pub fn pow(&self, exp: &BigInt) -> ClassGroupElement {
+        if exp.is_negative() {
+            return self.inverse().pow(&-exp);
+        }
         if exp.is_zero() {
             return Self::identity(self.discriminant.clone());
         }
-        
-        if exp == &BigInt::one() {
+        if exp.is_one() {
             return self.clone();
         }
         
-        let mut result = Self::identity(self.discriminant.clone());
-        let mut base = self.clone();
-        let mut exp = exp.clone();
+        let window = wnaf_window(exp.bits());
+        let digits = wnaf(exp.magnitude(), window);
+        let table = self.odd_powers(window);
         
-        while !exp.is_zero() {
-            if &exp % 2 == BigInt::one() {
-                result = result.compose(&base);
+        // The leading wNAF digit is always positive
+        let mut digits = digits.iter().rev();
+        let top = *digits.next().expect("non-zero exponent has a wNAF digit");
+        let mut result = table[(top / 2) as usize].clone();
+        for &digit in digits {
+            result = result.square();
+            if digit > 0 {
+                result = result.compose(&table[(digit / 2) as usize]);
+            } else if digit < 0 {
+                result = result.compose(&table[(-digit / 2) as usize].inverse());
             }
-            base = base.square();
-            exp >>= 1;
         }
         
         result
     }
 
+    /// Simultaneous multi-exponentiation: the product of base_i^exp_i
+    /// 
+    /// All terms share one chain of squarings (Shamir/Straus interleaving). Two
+    /// terms, the shape of the Wesolowski verification equation π^l·g^r, are recoded
+    /// in joint sparse form, so on average only half of the digit positions need a
+    /// composition from the table {b1, b2, b1·b2, b1/b2} and its free inverses.
+    /// Three or more terms use one wNAF per exponent. Negative exponents are
+    /// handled by inverting the base.
+    /// 
+    /// # Panics
+    /// Panics if `terms` is empty or the bases have different discriminants.
+    pub fn multi_pow(terms: &[(&ClassGroupElement, &BigInt)]) -> ClassGroupElement {
+        let (first, _) = terms.first().expect("multi_pow needs at least one term");
+        let discriminant = &first.discriminant;
+        
+        let mut bases = Vec::with_capacity(terms.len());
+        let mut exps = Vec::with_capacity(terms.len());
+        for &(base, exp) in terms {
+            assert_eq!(&base.discriminant, discriminant);
+            if exp.is_zero() {
+                continue;
+            }
+            if exp.is_negative() {
+                bases.push(base.inverse());
+            } else {
+                bases.push(base.clone());
+            }
+            exps.push(exp.magnitude().clone());
+        }
+        
+        match bases.len() {
+            0 => Self::identity(discriminant.clone()),
+            1 => bases[0].pow(&BigInt::from(exps[0].clone())),
+            2 => Self::joint_sparse_pow(&bases[0], &bases[1], &exps[0], &exps[1]),
+            _ => Self::straus_pow(&bases, &exps),
+        }
+    }
+
+    /// Shamir's trick over the joint sparse form of (k1, k2)
+    fn joint_sparse_pow(b1: &ClassGroupElement, b2: &ClassGroupElement, k1: &BigUint, k2: &BigUint) -> ClassGroupElement {
+        let digits = joint_sparse_form(k1, k2);
+        let sum = b1.compose(b2);
+        let difference = b1.div(b2);
+        let lookup = |u1: i8, u2: i8| -> ClassGroupElement {
+            match (u1, u2) {
+                (1, 0) => b1.clone(),
+                (-1, 0) => b1.inverse(),
+                (0, 1) => b2.clone(),
+                (0, -1) => b2.inverse(),
+                (1, 1) => sum.clone(),
+                (-1, -1) => sum.inverse(),
+                (1, -1) => difference.clone(),
+                (-1, 1) => difference.inverse(),
+                _ => unreachable!("joint sparse form digits are in {{-1, 0, 1}}"),
+            }
+        };
+        
+        let mut result: Option<ClassGroupElement> = None;
+        for &(u1, u2) in digits.iter().rev() {
+            if let Some(current) = result.as_mut() {
+                *current = current.square();
+            }
+            if u1 != 0 || u2 != 0 {
+                let factor = lookup(u1, u2);
+                result = Some(match result {
+                    Some(current) => current.compose(&factor),
+                    None => factor,
+                });
+            }
+        }
+        
+        result.unwrap_or_else(|| Self::identity(b1.discriminant.clone()))
+    }
+
+    /// Straus' interleaved exponentiation with one wNAF recoding per exponent
+    fn straus_pow(bases: &[ClassGroupElement], exps: &[BigUint]) -> ClassGroupElement {
+        let recodings: Vec<(Vec<i64>, Vec<ClassGroupElement>)> = bases
+            .iter()
+            .zip(exps)
+            .map(|(base, exp)| {
+                let window = wnaf_window(exp.bits());
+                (wnaf(exp, window), base.odd_powers(window))
+            })
+            .collect();
+        let length = recodings.iter().map(|(digits, _)| digits.len()).max().unwrap_or(0);
+        
+        let mut result: Option<ClassGroupElement> = None;
+        for position in (0..length).rev() {
+            if let Some(current) = result.as_mut() {
+                *current = current.square();
+            }
+            for (digits, table) in &recodings {
+                let digit = digits.get(position).copied().unwrap_or(0);
+                if digit == 0 {
+                    continue;
+                }
+                let factor = if digit > 0 {
+                    table[(digit / 2) as usize].clone()
+                } else {
+                    table[(-digit / 2) as usize].inverse()
+                };
+                result = Some(match result {
+                    Some(current) => current.compose(&factor),
+                    None => factor,
+                });
+            }
+        }
+        
+        result.unwrap_or_else(|| Self::identity(bases[0].discriminant.clone()))
+    }
+
+    /// Precompute the odd powers self, self^3, ..., self^(2^(w-1) - 1) for wNAF
+    fn odd_powers(&self, window: usize) -> Vec<ClassGroupElement> {
+        let count = 1usize << (window - 2);
+        let mut table = Vec::with_capacity(count);
+        table.push(self.clone());
+        if count > 1 {
+            let square = self.square();
+            for i in 1..count {
+                let next = table[i - 1].compose(&square);
+                table.push(next);
+            }
+        }
+        table
+    }
+
     /// Serialize the element for proof generation and storage
     /// 
     /// Returns a byte representation that can be used in cryptographic protocols.
@@ -230,59 +571,607 @@ impl ClassGroupElement {
     /// Deserialize element from bytes
     /// 
     /// Reconstructs a ClassGroupElement from its serialized representation.
-    /// Returns None if the bytes are malformed.
+    /// Returns None if the bytes are malformed; see `try_deserialize` for the
+    /// checks and the reason of a rejection.
     pub fn deserialize(bytes: &[u8], discriminant: &BigInt) -> Option<Self> {
-        if bytes.len() < 15 { // Minimum size for 3 length fields + 3 sign bytes
-            return None;
+        Self::try_deserialize(bytes, discriminant).ok()
+    }
+
+    /// Deserialize element from bytes, reporting why malformed input is rejected
+    /// 
+    /// The input must hold exactly the encoding written by `serialize` of a reduced
+    /// form of `discriminant`:
+    /// 
+    /// - no coefficient is longer than |Δ|, so length fields cannot claim more
+    ///   data than a form of this discriminant needs
+    /// - sign bytes are 0 or 1, magnitudes have no leading zero bytes and zero is
+    ///   not negative
+    /// - no bytes follow the third coefficient
+    /// - a > 0, b² - 4ac = Δ and the form is reduced
+    pub fn try_deserialize(bytes: &[u8], discriminant: &BigInt) -> Result<Self, VdfError> {
+        let max_len = discriminant.bits().div_ceil(8) as usize;
+        let mut offset = 0;
+        let a = read_signed(bytes, &mut offset, max_len)?;
+        let b = read_signed(bytes, &mut offset, max_len)?;
+        let c = read_signed(bytes, &mut offset, max_len)?;
+        if offset != bytes.len() {
+            return Err(VdfError::TrailingBytes(bytes.len() - offset));
         }
         
+        let element = Self::try_new(a, b, c, discriminant.clone())?;
+        if !element.is_reduced() {
+            return Err(VdfError::InvalidForm(FormError::NotReduced));
+        }
+        Ok(element)
+    }
+
+    /// Decode the layout of `serialize` without knowing Δ in advance
+    /// 
+    /// The discriminant is taken to be b² - 4ac of the decoded coefficients, and the
+    /// input must then pass every check of `try_deserialize`. A reduced form with
+    /// a > 0 always has Δ < 0, so the result is positive definite.
+    #[cfg(feature = "serde")]
+    pub(crate) fn try_deserialize_implicit(bytes: &[u8]) -> Result<Self, VdfError> {
         let mut offset = 0;
+        let a = read_signed(bytes, &mut offset, bytes.len())?;
+        let b = read_signed(bytes, &mut offset, bytes.len())?;
+        let c = read_signed(bytes, &mut offset, bytes.len())?;
+        let discriminant = &b * &b - 4 * &a * &c;
+        Self::try_deserialize(bytes, &discriminant)
+    }
+
+    /// Encode the reduced form equivalent to this element in the canonical fixed-size layout
+    /// 
+    /// The output is a flag byte, 1 if b is negative and 0 otherwise, followed by a
+    /// and |b| as big-endian integers of ⌈bits(|Δ|)/16⌉ bytes each. c is implied by
+    /// a, b and Δ. Reduced forms have |b| ≤ a < √|Δ|, so both always fit.
+    pub fn to_canonical_bytes(&self) -> Vec<u8> {
+        let form = self.reduced();
+        let width = canonical_width(&form.discriminant);
+        let mut out = Vec::with_capacity(1 + 2 * width);
+        out.push(u8::from(form.b.is_negative()));
+        for value in [&form.a, &form.b] {
+            let (_, magnitude) = value.to_bytes_be();
+            out.resize(out.len() + width - magnitude.len(), 0);
+            out.extend_from_slice(&magnitude);
+        }
+        out
+    }
+
+    /// Decode the canonical fixed-size layout of `to_canonical_bytes`
+    /// 
+    /// Rejects input of the wrong length, flag bytes other than 0 and 1, a negative
+    /// zero, and coefficients that are not a reduced form of `discriminant`.
+    pub fn from_canonical_bytes(bytes: &[u8], discriminant: &BigInt) -> Result<Self, VdfError> {
+        let width = canonical_width(discriminant);
+        if bytes.len() != 1 + 2 * width {
+            return Err(VdfError::MalformedEncoding("wrong length for the discriminant"));
+        }
+        let sign = match bytes[0] {
+            0 => Sign::Plus,
+            1 => Sign::Minus,
+            _ => return Err(VdfError::MalformedEncoding("invalid sign byte")),
+        };
+        let a = BigInt::from_bytes_be(Sign::Plus, &bytes[1..1 + width]);
+        let b = BigInt::from_bytes_be(sign, &bytes[1 + width..]);
+        if sign == Sign::Minus && b.is_zero() {
+            return Err(VdfError::MalformedEncoding("negative zero"));
+        }
+        Self::from_reduced_coefficients(a, b, discriminant)
+    }
+
+    /// Encode the reduced form equivalent to this element in Chia's compressed layout
+    /// 
+    /// Follows `bqfc_serialize` of chiavdf byte for byte. A reduced form (a, b) is
+    /// represented by a' = a/g, t' = t/g, g = gcd(a, t) and b0 = ⌊|b|/a'⌋, where t
+    /// is the cofactor of b at the point where the Euclidean algorithm on (a, |b|)
+    /// drops below √a. The decoder recovers b from b·t ≡ √(t²·Δ) (mod a'), so only
+    /// about half of b has to be stored. The identity (1, 1) and the form (2, 1)
+    /// are encoded by a flag alone. Integers are little-endian, and the output has
+    /// 3·⌈bits(|Δ|)/32⌉ + 4 bytes, but at least 100.
+    pub fn to_compressed_bytes(&self) -> Vec<u8> {
+        let form = self.reduced();
+        let bits = form.discriminant.bits() as usize;
+        let mut out = vec![0u8; compressed_size(bits)];
         
-        // Read a
-        if offset + 4 > bytes.len() { return None; }
-        let a_len = u32::from_be_bytes([bytes[offset], bytes[offset+1], bytes[offset+2], bytes[offset+3]]) as usize;
-        offset += 4;
+        if form.b.is_one() && form.a <= BigInt::from(2) {
+            out[0] = if form.a == BigInt::from(2) { BQFC_IS_GEN } else { BQFC_IS_1 };
+            return out;
+        }
         
-        if offset + 1 > bytes.len() { return None; }
-        let a_sign = if bytes[offset] == 1 { Sign::Minus } else { Sign::Plus };
-        offset += 1;
+        let compressed = CompressedForm::compress(&form.a, &form.b);
+        let d_bits = bits.next_multiple_of(32);
+        let g_size = (compressed.g.bits().max(1) as usize).div_ceil(8) - 1;
+        if compressed.b_sign {
+            out[0] |= BQFC_B_SIGN;
+        }
+        if compressed.t.is_negative() {
+            out[0] |= BQFC_T_SIGN;
+        }
+        out[1] = g_size as u8;
         
-        if offset + a_len > bytes.len() { return None; }
-        let a = BigInt::from_bytes_be(a_sign, &bytes[offset..offset + a_len]);
-        offset += a_len;
+        let mut offset = 2;
+        for (value, size) in [
+            (&compressed.a, d_bits / 16 - g_size),
+            (&compressed.t, d_bits / 32 - g_size),
+            (&compressed.g, g_size + 1),
+            (&compressed.b0, g_size + 1),
+        ] {
+            let (_, magnitude) = value.to_bytes_le();
+            if !value.is_zero() {
+                out[offset..offset + magnitude.len()].copy_from_slice(&magnitude);
+            }
+            offset += size;
+        }
+        out
+    }
+
+    /// Decode Chia's compressed layout of `to_compressed_bytes`
+    /// 
+    /// Besides decompressing, the result is re-encoded and compared with the input,
+    /// so only the one canonical encoding of a reduced form is accepted.
+    pub fn from_compressed_bytes(bytes: &[u8], discriminant: &BigInt) -> Result<Self, VdfError> {
+        let bits = discriminant.bits() as usize;
+        if bytes.len() != compressed_size(bits) {
+            return Err(VdfError::MalformedEncoding("wrong length for the discriminant"));
+        }
         
-        // Read b
-        if offset + 4 > bytes.len() { return None; }
-        let b_len = u32::from_be_bytes([bytes[offset], bytes[offset+1], bytes[offset+2], bytes[offset+3]]) as usize;
-        offset += 4;
+        let (a, b) = if bytes[0] & (BQFC_IS_1 | BQFC_IS_GEN) != 0 {
+            let a = if bytes[0] & BQFC_IS_GEN != 0 { 2 } else { 1 };
+            (BigInt::from(a), BigInt::one())
+        } else {
+            let d_bits = bits.next_multiple_of(32);
+            let g_size = bytes[1] as usize;
+            if g_size >= d_bits / 32 {
+                return Err(VdfError::MalformedEncoding("gcd is too large"));
+            }
+            
+            let mut offset = 2;
+            let mut read = |size: usize| {
+                let value = BigInt::from_bytes_le(Sign::Plus, &bytes[offset..offset + size]);
+                offset += size;
+                value
+            };
+            let a = read(d_bits / 16 - g_size);
+            let mut t = read(d_bits / 32 - g_size);
+            let g = read(g_size + 1);
+            let b0 = read(g_size + 1);
+            if bytes[0] & BQFC_T_SIGN != 0 {
+                t = -t;
+            }
+            let compressed = CompressedForm { a, t, g, b0, b_sign: bytes[0] & BQFC_B_SIGN != 0 };
+            compressed.decompress(discriminant)?
+        };
         
-        if offset + 1 > bytes.len() { return None; }
-        let b_sign = if bytes[offset] == 1 { Sign::Minus } else { Sign::Plus };
-        offset += 1;
+        let form = Self::from_reduced_coefficients(a, b, discriminant)?;
+        if form.to_compressed_bytes() != bytes {
+            return Err(VdfError::MalformedEncoding("non-canonical compressed form"));
+        }
+        Ok(form)
+    }
+
+    /// Complete (a, b) to a form of `discriminant` and require it to be reduced
+    fn from_reduced_coefficients(a: BigInt, b: BigInt, discriminant: &BigInt) -> Result<Self, VdfError> {
+        if !a.is_positive() {
+            return Err(VdfError::InvalidForm(FormError::NonPositiveLeadingCoefficient));
+        }
+        let (c, remainder) = (&b * &b - discriminant).div_rem(&(4 * &a));
+        if !remainder.is_zero() {
+            return Err(VdfError::InvalidForm(FormError::DiscriminantMismatch));
+        }
+        let form = Self { a, b, c, discriminant: discriminant.clone() };
+        if !form.is_reduced() {
+            return Err(VdfError::InvalidForm(FormError::NotReduced));
+        }
+        Ok(form)
+    }
+
+}
+
+impl Mul<&ClassGroupElement> for &ClassGroupElement {
+    type Output = ClassGroupElement;
+
+    fn mul(self, rhs: &ClassGroupElement) -> ClassGroupElement {
+        self.compose(rhs)
+    }
+}
+
+impl Mul for ClassGroupElement {
+    type Output = ClassGroupElement;
+
+    fn mul(self, rhs: ClassGroupElement) -> ClassGroupElement {
+        self.compose(&rhs)
+    }
+}
+
+impl Neg for &ClassGroupElement {
+    type Output = ClassGroupElement;
+
+    fn neg(self) -> ClassGroupElement {
+        self.inverse()
+    }
+}
+
+impl Neg for ClassGroupElement {
+    type Output = ClassGroupElement;
+
+    fn neg(self) -> ClassGroupElement {
+        self.inverse()
+    }
+}
+
+/// Read one length-prefixed, sign-tagged integer of the serialized form layout
+/// 
+/// The length may not exceed `max_len`, and the integer must be in its unique
+/// minimal encoding.
+fn read_signed(bytes: &[u8], offset: &mut usize, max_len: usize) -> Result<BigInt, VdfError> {
+    let header = bytes
+        .get(*offset..*offset + 5)
+        .ok_or(VdfError::MalformedEncoding("truncated length or sign"))?;
+    let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+    if len > max_len {
+        return Err(VdfError::MalformedEncoding("coefficient is longer than the discriminant"));
+    }
+    let sign = match header[4] {
+        0 => Sign::Plus,
+        1 => Sign::Minus,
+        _ => return Err(VdfError::MalformedEncoding("invalid sign byte")),
+    };
+    *offset += 5;
+    
+    let magnitude = bytes
+        .get(*offset..*offset + len)
+        .ok_or(VdfError::MalformedEncoding("truncated coefficient"))?;
+    *offset += len;
+    match magnitude {
+        [] => Err(VdfError::MalformedEncoding("empty coefficient")),
+        [0, _, ..] => Err(VdfError::MalformedEncoding("leading zero byte")),
+        [0] if sign == Sign::Minus => Err(VdfError::MalformedEncoding("negative zero")),
+        _ => Ok(BigInt::from_bytes_be(sign, magnitude)),
+    }
+}
+
+/// Bytes per coefficient in the canonical encoding: ⌈bits(|Δ|)/16⌉
+fn canonical_width(discriminant: &BigInt) -> usize {
+    (discriminant.bits() as usize).div_ceil(16)
+}
+
+/// Flag of a compressed form with negative b
+const BQFC_B_SIGN: u8 = 1 << 0;
+/// Flag of a compressed form with negative t
+const BQFC_T_SIGN: u8 = 1 << 1;
+/// Flag of the compressed identity (1, 1)
+const BQFC_IS_1: u8 = 1 << 2;
+/// Flag of the compressed form (2, 1)
+const BQFC_IS_GEN: u8 = 1 << 3;
+
+/// Size of compressed forms, at least the 100 bytes of 1024-bit discriminants
+fn compressed_size(discriminant_bits: usize) -> usize {
+    discriminant_bits.max(1024).div_ceil(32) * 3 + 4
+}
+
+/// Compressed representation of a reduced form, as in chiavdf's `qfb_c`
+struct CompressedForm {
+    a: BigInt,
+    t: BigInt,
+    g: BigInt,
+    b0: BigInt,
+    b_sign: bool,
+}
+
+impl CompressedForm {
+    /// `bqfc_compr`: compress the reduced form (a, b)
+    fn compress(a: &BigInt, b: &BigInt) -> Self {
+        if a == b {
+            return Self {
+                a: a.clone(),
+                t: BigInt::zero(),
+                g: BigInt::zero(),
+                b0: BigInt::zero(),
+                b_sign: false,
+            };
+        }
         
-        if offset + b_len > bytes.len() { return None; }
-        let b = BigInt::from_bytes_be(b_sign, &bytes[offset..offset + b_len]);
-        offset += b_len;
+        let t = -partial_euclid_cofactor(a, &b.abs(), &a.sqrt());
+        let g = a.gcd(&t);
+        if g.is_one() {
+            return Self { a: a.clone(), t, g, b0: BigInt::zero(), b_sign: b.is_negative() };
+        }
         
-        // Read c
-        if offset + 4 > bytes.len() { return None; }
-        let c_len = u32::from_be_bytes([bytes[offset], bytes[offset+1], bytes[offset+2], bytes[offset+3]]) as usize;
-        offset += 4;
+        let reduced_a = a / &g;
+        let b0 = b.abs() / &reduced_a;
+        Self { t: t / &g, a: reduced_a, g, b0, b_sign: b.is_negative() }
+    }
+
+    /// `bqfc_decompr`: recover (a, b) given the discriminant
+    fn decompress(&self, discriminant: &BigInt) -> Result<(BigInt, BigInt), VdfError> {
+        if self.t.is_zero() {
+            return Ok((self.a.clone(), self.a.clone()));
+        }
+        if self.a.is_zero() {
+            return Err(VdfError::MalformedEncoding("zero leading coefficient"));
+        }
         
-        if offset + 1 > bytes.len() { return None; }
-        let c_sign = if bytes[offset] == 1 { Sign::Minus } else { Sign::Plus };
-        offset += 1;
+        let t = if self.t.is_negative() { &self.t + &self.a } else { self.t.clone() };
+        let gcd = t.extended_gcd(&self.a);
+        if !gcd.gcd.is_one() {
+            return Err(VdfError::MalformedEncoding("t is not invertible"));
+        }
+        let t_inverse = gcd.x.mod_floor(&self.a);
         
-        if offset + c_len > bytes.len() { return None; }
-        let c = BigInt::from_bytes_be(c_sign, &bytes[offset..offset + c_len]);
+        // b·t ≡ √(t²·Δ) (mod a')
+        let square = (&self.t * &self.t).mod_floor(&self.a) * discriminant.mod_floor(&self.a) % &self.a;
+        let root = square.sqrt();
+        if &root * &root != square {
+            return Err(VdfError::MalformedEncoding("no square root of t²·Δ"));
+        }
         
-        Some(Self {
-            a,
-            b,
-            c,
-            discriminant: discriminant.clone(),
-        })
+        let mut b = root * t_inverse % &self.a;
+        let a = if self.g > BigInt::one() { &self.a * &self.g } else { self.a.clone() };
+        if self.b0.is_positive() {
+            b += &self.a * &self.b0;
+        }
+        if self.b_sign {
+            b = -b;
+        }
+        Ok((a, b))
+    }
+}
+
+/// Cofactor of r1 when the Euclidean algorithm on (r2, r1) first has r1 ≤ bound
+/// 
+/// The result of chiavdf's `mpz_xgcd_partial`, which computes the same quotient
+/// sequence with Lehmer steps: starting from cofactors 0 and -1, each step
+/// replaces (r2, r1) by (r1, r2 mod r1) and the cofactors alike.
+fn partial_euclid_cofactor(r2: &BigInt, r1: &BigInt, bound: &BigInt) -> BigInt {
+    let (mut r2, mut r1) = (r2.clone(), r1.clone());
+    let (mut co2, mut co1) = (BigInt::zero(), -BigInt::one());
+    while !r1.is_zero() && &r1 > bound {
+        let (q, r) = r2.div_mod_floor(&r1);
+        r2 = std::mem::replace(&mut r1, r);
+        let next = &co2 - &q * &co1;
+        co2 = std::mem::replace(&mut co1, next);
+    }
+    co1
+}
+
+/// Pick the wNAF window width for an exponent of the given bit length
+/// 
+/// A width-w recoding costs 2^(w-2) compositions for the table and about
+/// bits/(w+1) compositions during the scan; these thresholds minimise the sum.
+fn wnaf_window(bits: u64) -> usize {
+    match bits {
+        0..=24 => 2,
+        25..=80 => 3,
+        81..=200 => 4,
+        201..=560 => 5,
+        _ => 6,
+    }
+}
+
+/// Recode a non-negative integer in width-w non-adjacent form
+/// 
+/// Returns the digits least significant first. Every non-zero digit is odd with
+/// absolute value below 2^(w-1), and any w consecutive digits contain at most one
+/// non-zero digit.
+fn wnaf(exp: &BigUint, window: usize) -> Vec<i64> {
+    let modulus = 1i64 << window;
+    let half = modulus >> 1;
+    let mut k = exp.clone();
+    let mut digits = Vec::with_capacity(exp.bits() as usize + 1);
+    
+    while !k.is_zero() {
+        let digit = if k.bit(0) {
+            let low = (k.iter_u64_digits().next().unwrap_or(0) & (modulus as u64 - 1)) as i64;
+            let digit = if low >= half { low - modulus } else { low };
+            if digit > 0 {
+                k -= digit as u64;
+            } else {
+                k += (-digit) as u64;
+            }
+            digit
+        } else {
+            0
+        };
+        digits.push(digit);
+        k >>= 1;
+    }
+    
+    digits
+}
+
+/// Solinas' joint sparse form of a pair of non-negative integers
+/// 
+/// Returns digit pairs (u1, u2) ∈ {-1, 0, 1}², least significant first. Of any
+/// three consecutive positions at least one is (0, 0), and on average half of all
+/// positions are, which is optimal for two simultaneous exponents.
+fn joint_sparse_form(k1: &BigUint, k2: &BigUint) -> Vec<(i8, i8)> {
+    let mut k = [k1.clone(), k2.clone()];
+    let mut d = [0u8; 2];
+    let mut digits = Vec::with_capacity(k1.bits().max(k2.bits()) as usize + 1);
+    let low_bits = |x: &BigUint| (x.iter_u64_digits().next().unwrap_or(0) & 7) as u8;
+    
+    while !(k[0].is_zero() && d[0] == 0 && k[1].is_zero() && d[1] == 0) {
+        // ℓ_i = d_i + k_i, of which only the residue mod 8 matters
+        let l = [(d[0] + low_bits(&k[0])) & 7, (d[1] + low_bits(&k[1])) & 7];
+        let mut u = [0i8; 2];
+        for i in 0..2 {
+            if l[i] & 1 == 1 {
+                u[i] = if l[i] & 3 == 1 { 1 } else { -1 };
+                if (l[i] == 3 || l[i] == 5) && l[1 - i] & 3 == 2 {
+                    u[i] = -u[i];
+                }
+            }
+        }
+        for i in 0..2 {
+            if 2 * d[i] as i8 == 1 + u[i] {
+                d[i] = 1 - d[i];
+            }
+            k[i] >>= 1;
+        }
+        digits.push((u[0], u[1]));
     }
+    
+    digits
+}
+
+/// Partial reduction bound for NUCOMP: ≈ √(a1/a2)·|Δ/4|^(1/4)
+/// 
+/// Only the size of the bound matters for efficiency and any positive bound gives
+/// a correct result, so it is approximated from bit lengths to keep it cheap.
+fn nucomp_bound(a1: &BigInt, a2: &BigInt, discriminant: &BigInt) -> BigInt {
+    let log_a1 = a1.bits() as i64;
+    let log_a2 = a2.bits() as i64;
+    let log_d = discriminant.bits() as i64 - 2;
+    let shift = ((log_a1 - log_a2) / 2 + log_d / 4).max(0);
+    BigInt::one() << shift as usize
+}
+
+/// Shared tail of NUCOMP and NUDUPL
+/// 
+/// Given the composition data A = a1'·a2', B = b2 + 2·a2'·U (with U taken mod a1')
+/// and the common factor d1, this runs the partial Euclidean reduction of (a1', U)
+/// and rebuilds the almost reduced form from the last two remainders R and
+/// cofactors C without ever forming A, B or C themselves:
+/// 
+/// - a = (a2'·R² - b2·R·C + d1·c2·C²) / a1'
+/// - b = (2·δ·C_prev·a + 2·a2'·R - b2·C) / C, with δ = (-1)^(i+1) after i steps
+#[allow(clippy::too_many_arguments)]
+fn nucomp_finish(
+    a1: &BigInt,
+    a2: &BigInt,
+    d1: &BigInt,
+    b2: &BigInt,
+    c2: &BigInt,
+    u: BigInt,
+    bound: &BigInt,
+    discriminant: &BigInt,
+) -> ClassGroupElement {
+    // Partial extended Euclid on (R_-1, R_0) = (a1', U) with (C_-1, C_0) = (0, -1)
+    let mut r_prev = a1.clone();
+    let mut r = u;
+    let mut c_prev = BigInt::zero();
+    let mut c = -BigInt::one();
+    let mut steps = 0usize;
+    
+    // While the remainders are far above the bound, whole batches of quotients are
+    // taken from the leading 63 bits (Lehmer); this can never step past the bound
+    // because one batch shrinks R_-1 by at most 65 bits.
+    let lehmer_limit = bound.bits() + 66;
+    while &r >= bound && !r.is_zero() {
+        if r_prev.bits() > lehmer_limit {
+            let shift = r_prev.bits() - 63;
+            let (m, inner) = lehmer_matrix(&r_prev, &r, shift);
+            if inner > 0 {
+                (r_prev, r) = m.apply(&r_prev, &r);
+                (c_prev, c) = m.apply(&c_prev, &c);
+                steps += inner;
+                continue;
+            }
+        }
+        let (q, rem) = r_prev.div_rem(&r);
+        r_prev = std::mem::replace(&mut r, rem);
+        let c_next = &c_prev - &q * &c;
+        c_prev = std::mem::replace(&mut c, c_next);
+        steps += 1;
+    }
+    
+    let mut result = if steps == 0 {
+        // No reduction took place: the product form is already small enough
+        let a3 = a1 * a2;
+        let b3 = b2 + 2 * a2 * &r;
+        let c3 = (&b3 * &b3 - discriminant) / (4 * &a3);
+        ClassGroupElement { a: a3, b: b3, c: c3, discriminant: discriminant.clone() }
+    } else {
+        let a3 = (a2 * &r * &r - b2 * &r * &c + d1 * c2 * &c * &c) / a1;
+        let mut b_num = 2 * a2 * &r - b2 * &c;
+        if steps % 2 == 1 {
+            b_num += 2 * &c_prev * &a3;
+        } else {
+            b_num -= 2 * &c_prev * &a3;
+        }
+        let b3 = b_num / &c;
+        let c3 = (&b3 * &b3 - discriminant) / (4 * &a3);
+        ClassGroupElement { a: a3, b: b3, c: c3, discriminant: discriminant.clone() }
+    };
+    result.reduce();
+    result
+}
+
+/// 2×2 cofactor matrix accumulated by one round of Lehmer's algorithm
+struct LehmerMatrix {
+    a: i64,
+    b: i64,
+    c: i64,
+    d: i64,
+}
+
+impl LehmerMatrix {
+    /// Map (x, y) to (a·x + b·y, c·x + d·y)
+    fn apply(&self, x: &BigInt, y: &BigInt) -> (BigInt, BigInt) {
+        (x * self.a + y * self.b, x * self.c + y * self.d)
+    }
+}
+
+/// Run Euclid's algorithm on the leading bits of (u, v), u ≥ v ≥ 0
+/// 
+/// This is step L2/L3 of Knuth's Algorithm L: quotients are accepted only while the
+/// two single-precision estimates agree, so every step taken is one the full
+/// precision algorithm would also take. Returns the accumulated matrix together
+/// with the number of steps, which is zero when no quotient could be certified.
+fn lehmer_matrix(u: &BigInt, v: &BigInt, shift: u64) -> (LehmerMatrix, usize) {
+    let mut uh: i128 = (u >> shift).try_into().unwrap_or(0);
+    let mut vh: i128 = (v >> shift).try_into().unwrap_or(0);
+    let (mut a, mut b, mut c, mut d) = (1i128, 0i128, 0i128, 1i128);
+    let mut steps = 0;
+    
+    while vh + c != 0 && vh + d != 0 {
+        let q = (uh + a) / (vh + c);
+        if q != (uh + b) / (vh + d) {
+            break;
+        }
+        (a, c) = (c, a - q * c);
+        (b, d) = (d, b - q * d);
+        (uh, vh) = (vh, uh - q * vh);
+        steps += 1;
+    }
+    
+    let matrix = LehmerMatrix { a: a as i64, b: b as i64, c: c as i64, d: d as i64 };
+    (matrix, steps)
+}
+
+/// Lehmer extended gcd that only tracks the cofactor of `a`
+/// 
+/// Returns (g, x) with g = gcd(a, b) ≥ 0 and x·a ≡ g (mod b). NUCOMP and NUDUPL need
+/// nothing else, and dropping the second cofactor saves a third of the multiplications.
+fn lehmer_gcd_cofactor(a: &BigInt, b: &BigInt) -> (BigInt, BigInt) {
+    // Invariant: u ≡ su·|a| and v ≡ sv·|a| (mod |b|)
+    let (mut u, mut su) = (b.abs(), BigInt::zero());
+    let (mut v, mut sv) = (a.abs(), BigInt::one());
+    if u < v {
+        std::mem::swap(&mut u, &mut v);
+        std::mem::swap(&mut su, &mut sv);
+    }
+    
+    while !v.is_zero() {
+        if u.bits() > 63 {
+            let (m, steps) = lehmer_matrix(&u, &v, u.bits() - 63);
+            if steps > 0 {
+                (u, v) = m.apply(&u, &v);
+                (su, sv) = m.apply(&su, &sv);
+                continue;
+            }
+        }
+        let (q, r) = u.div_rem(&v);
+        u = std::mem::replace(&mut v, r);
+        let s_next = &su - &q * &sv;
+        su = std::mem::replace(&mut sv, s_next);
+    }
+    
+    if a.is_negative() {
+        su = -su;
+    }
+    (u, su)
 }
 
 /// Compute the greatest common divisor of two BigInts
@@ -303,7 +1192,7 @@ pub fn gcd(a: &BigInt, b: &BigInt) -> BigInt {
 
 /// Extended Euclidean algorithm (iterative to avoid stack overflow)
 /// 
-/// Returns (gcd, x, y) such that ax + by = gcd(a, b)
+/// Returns (gcd, x, y) such that ax + by = gcd(a, b), with gcd(a, b) ≥ 0
 /// Used in class group composition for computing Bezout coefficients.
 pub fn extended_gcd(a: &BigInt, b: &BigInt) -> (BigInt, BigInt, BigInt) {
     let mut old_r = a.clone();
@@ -328,5 +1217,265 @@ pub fn extended_gcd(a: &BigInt, b: &BigInt) -> (BigInt, BigInt, BigInt) {
         old_t = temp_t;
     }
     
-    (old_r, old_s, old_t)
-}
\ No newline at end of file
+    if old_r.is_negative() {
+        (-old_r, -old_s, -old_t)
+    } else {
+        (old_r, old_s, old_t)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::create_discriminant;
+
+    /// Forms hashed into the class group of a prime discriminant of `bits` bits
+    fn forms(bits: usize, count: u32) -> Vec<ClassGroupElement> {
+        let discriminant = create_discriminant(format!("compose-reference-{}", bits).as_bytes(), bits);
+        (0..count)
+            .map(|i| ClassGroupElement::from_hash(discriminant.clone(), &i.to_be_bytes()).unwrap())
+            .collect()
+    }
+
+    /// Square-and-multiply with `compose_reference` only
+    fn pow_reference(base: &ClassGroupElement, exp: u64) -> ClassGroupElement {
+        let mut result = ClassGroupElement::identity(base.discriminant.clone());
+        for bit in (0..64).rev() {
+            result = result.compose_reference(&result);
+            if (exp >> bit) & 1 == 1 {
+                result = result.compose_reference(base);
+            }
+        }
+        result
+    }
+
+    const EXPONENTS: [u64; 8] = [0, 1, 2, 3, 7, 1000, 0xdead_beef, 0x9e37_79b9_7f4a_7c15];
+
+    #[test]
+    fn compose_matches_reference() {
+        for bits in [128, 512, 1024] {
+            let forms = forms(bits, 6);
+            for f in &forms {
+                for g in &forms {
+                    assert_eq!(f.compose(g), f.compose_reference(g), "{} bits", bits);
+                }
+                assert!(f.compose(&f.inverse()).is_identity());
+            }
+        }
+    }
+
+    #[test]
+    fn square_matches_reference() {
+        for bits in [128, 512, 1024] {
+            for f in forms(bits, 6) {
+                let mut x = f.clone();
+                let mut y = f;
+                for _ in 0..20 {
+                    x = x.square();
+                    y = y.compose_reference(&y);
+                    assert_eq!(x, y, "{} bits", bits);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pow_matches_reference() {
+        for bits in [128, 512, 1024] {
+            let forms = forms(bits, 3);
+            for f in &forms {
+                for exp in EXPONENTS {
+                    let expected = pow_reference(f, exp);
+                    assert_eq!(f.pow(&BigInt::from(exp)), expected, "{} bits, exponent {}", bits, exp);
+                    assert_eq!(f.pow(&-BigInt::from(exp)), expected.inverse(), "{} bits, exponent -{}", bits, exp);
+                }
+            }
+            
+            let (f, g) = (&forms[0], &forms[1]);
+            for (e1, e2) in EXPONENTS.iter().zip(EXPONENTS.iter().rev()) {
+                let expected = pow_reference(f, *e1).compose_reference(&pow_reference(g, *e2));
+                let product = ClassGroupElement::multi_pow(&[(f, &BigInt::from(*e1)), (g, &BigInt::from(*e2))]);
+                assert_eq!(product, expected, "{} bits", bits);
+            }
+        }
+    }
+
+    #[test]
+    fn prime_forms_exist_exactly_for_residues() {
+        let discriminant = create_discriminant(b"prime-form", 512);
+        let mut found = 0;
+        for p in (2..200u32).map(BigInt::from).filter(is_probably_prime) {
+            let form = ClassGroupElement::prime_form(discriminant.clone(), &p);
+            if kronecker(&discriminant, &p) != 1 {
+                assert_eq!(form, None, "p = {}", p);
+                continue;
+            }
+            let form = form.unwrap();
+            assert!(form.is_valid() && form.is_reduced(), "p = {}", p);
+            assert_eq!(form.discriminant, discriminant);
+            assert_eq!(form.a, p);
+            assert!(form.b.is_positive());
+            found += 1;
+        }
+        assert!(found > 10);
+        
+        // Δ ≡ 1 (mod 8), so 2 splits and its prime form is the generator
+        assert_eq!(
+            ClassGroupElement::prime_form(discriminant.clone(), &BigInt::from(2)),
+            Some(ClassGroupElement::generator(discriminant.clone()))
+        );
+        for composite in [1, 9, 15, 91] {
+            assert_eq!(ClassGroupElement::prime_form(discriminant.clone(), &BigInt::from(composite)), None);
+        }
+    }
+
+    #[test]
+    fn from_hash_is_deterministic() {
+        let discriminant = create_discriminant(b"prime-form", 512);
+        let form = ClassGroupElement::from_hash(discriminant.clone(), b"input").unwrap();
+        assert!(form.is_valid() && form.is_reduced());
+        assert_eq!(form.discriminant, discriminant);
+        assert_eq!(ClassGroupElement::from_hash(discriminant.clone(), b"input"), Ok(form.clone()));
+        assert_ne!(ClassGroupElement::from_hash(discriminant, b"other input"), Ok(form));
+    }
+
+    #[test]
+    fn serialization_round_trips() {
+        for form in forms(512, 4).into_iter().chain(forms(1024, 2)) {
+            let bytes = form.serialize();
+            assert_eq!(ClassGroupElement::try_deserialize(&bytes, &form.discriminant), Ok(form.clone()));
+            assert_eq!(ClassGroupElement::deserialize(&bytes, &form.discriminant), Some(form.clone()));
+            let inverse = form.inverse();
+            assert_eq!(ClassGroupElement::try_deserialize(&inverse.serialize(), &form.discriminant), Ok(inverse));
+        }
+    }
+
+    #[test]
+    fn deserialize_rejects_malformed_input() {
+        let form = &forms(512, 1)[0];
+        let discriminant = &form.discriminant;
+        let valid = form.serialize();
+        let last = valid.len() - 1;
+        let malformed = VdfError::MalformedEncoding;
+        let with_bytes = |index: usize, values: &[u8]| {
+            let mut bytes = valid.clone();
+            bytes[index..index + values.len()].copy_from_slice(values);
+            bytes
+        };
+        let mut trailing = valid.clone();
+        trailing.push(0);
+        let mut leading_zero = valid.clone();
+        leading_zero.insert(5, 0);
+        leading_zero[3] += 1;
+        
+        let rejected = [
+            (valid[..3].to_vec(), malformed("truncated length or sign")),
+            (valid[..last].to_vec(), malformed("truncated coefficient")),
+            (trailing, VdfError::TrailingBytes(1)),
+            // A length field larger than |Δ| is rejected before any allocation
+            (with_bytes(0, &u32::MAX.to_be_bytes()), malformed("coefficient is longer than the discriminant")),
+            (with_bytes(0, &65u32.to_be_bytes()), malformed("coefficient is longer than the discriminant")),
+            (with_bytes(4, &[2]), malformed("invalid sign byte")),
+            (with_bytes(0, &[0, 0, 0, 0]), malformed("empty coefficient")),
+            (leading_zero, malformed("leading zero byte")),
+            (with_bytes(4, &[1]), VdfError::InvalidForm(FormError::NonPositiveLeadingCoefficient)),
+            (with_bytes(last, &[valid[last] ^ 1]), VdfError::InvalidForm(FormError::DiscriminantMismatch)),
+        ];
+        for (bytes, expected) in rejected {
+            assert_eq!(ClassGroupElement::try_deserialize(&bytes, discriminant), Err(expected));
+            assert_eq!(ClassGroupElement::deserialize(&bytes, discriminant), None);
+        }
+        
+        let other = create_discriminant(b"another discriminant", 512);
+        assert_eq!(
+            ClassGroupElement::try_deserialize(&valid, &other),
+            Err(VdfError::InvalidForm(FormError::DiscriminantMismatch))
+        );
+        
+        // (a, b + 2a, ...) is a valid form in the same class, but not reduced
+        let unreduced = ClassGroupElement::new(form.a.clone(), &form.b + 2 * &form.a, discriminant.clone());
+        assert!(unreduced.is_valid());
+        assert_eq!(
+            ClassGroupElement::try_deserialize(&unreduced.serialize(), discriminant),
+            Err(VdfError::InvalidForm(FormError::NotReduced))
+        );
+    }
+
+    /// g^(2^16) for the generator g of `create_discriminant(b"\xaa", 1024)`, and its
+    /// compressed encoding as produced by chiavdf's `bqfc_serialize`
+    const CHIAVDF_COMPRESSED: &str = concat!(
+        "000083ba2c4183ee17e0fa64760f54e15e173489f03fcd0bc29b0a9be7b3c25e8e53d7c9fbebc7ed06c7",
+        "d559af7246d5c7f8843c1b47745a06a7464b18c69181c64b771efa50023061836e8fb5491978f332ef07",
+        "a53b2f221bd0fad9bb6918db33150100",
+    );
+
+    fn compressed_forms() -> (BigInt, ClassGroupElement) {
+        let discriminant = create_discriminant(b"\xaa", 1024);
+        let mut form = ClassGroupElement::generator(discriminant.clone());
+        for _ in 0..16 {
+            form = form.square();
+        }
+        (discriminant, form)
+    }
+
+    #[test]
+    fn compressed_encodes_flagged_forms() {
+        let (discriminant, _) = compressed_forms();
+        let mut generator = vec![0u8; 100];
+        generator[0] = 0x08;
+        let mut identity = vec![0u8; 100];
+        identity[0] = 0x04;
+        
+        for (form, expected) in [
+            (ClassGroupElement::generator(discriminant.clone()), generator),
+            (ClassGroupElement::identity(discriminant.clone()), identity),
+        ] {
+            assert_eq!(form.to_compressed_bytes(), expected);
+            assert_eq!(ClassGroupElement::from_compressed_bytes(&expected, &discriminant), Ok(form));
+        }
+    }
+
+    #[test]
+    fn compressed_matches_chiavdf() {
+        let (discriminant, form) = compressed_forms();
+        let expected = hex::decode(CHIAVDF_COMPRESSED).unwrap();
+        assert_eq!(form.to_compressed_bytes(), expected);
+        assert_eq!(ClassGroupElement::from_compressed_bytes(&expected, &discriminant), Ok(form.clone()));
+        
+        // The inverse (a, -b) only differs in the sign flag of b
+        let mut inverse = expected;
+        inverse[0] = 0x01;
+        assert_eq!(form.inverse().to_compressed_bytes(), inverse);
+        assert_eq!(ClassGroupElement::from_compressed_bytes(&inverse, &discriminant), Ok(form.inverse()));
+    }
+
+    #[test]
+    fn compressed_rejects_non_canonical_input() {
+        let (discriminant, _) = compressed_forms();
+        let valid = hex::decode(CHIAVDF_COMPRESSED).unwrap();
+        let malformed = VdfError::MalformedEncoding;
+        let with_byte = |index: usize, value: u8| {
+            let mut bytes = valid.clone();
+            bytes[index] = value;
+            bytes
+        };
+        
+        let rejected = [
+            (valid[..99].to_vec(), malformed("wrong length for the discriminant")),
+            // Unknown flags, both special forms at once, or a flag with a sign
+            (with_byte(0, 0x10), malformed("non-canonical compressed form")),
+            (with_byte(0, 0x0c), malformed("non-canonical compressed form")),
+            (with_byte(0, 0x05), malformed("non-canonical compressed form")),
+            // A wrong size of g moves the fields
+            (with_byte(1, 1), malformed("no square root of t²·Δ")),
+            (with_byte(1, 31), malformed("t is not invertible")),
+            (with_byte(1, 32), malformed("gcd is too large")),
+            // b0 = ⌊|b|/a⌋ is the last byte: 2 decodes to (a, b + 2a), which is in
+            // the same class but not reduced as b > a, and 1 to a b of wrong parity
+            (with_byte(99, 2), VdfError::InvalidForm(FormError::NotReduced)),
+            (with_byte(99, 1), VdfError::InvalidForm(FormError::DiscriminantMismatch)),
+        ];
+        for (bytes, expected) in rejected {
+            assert_eq!(ClassGroupElement::from_compressed_bytes(&bytes, &discriminant), Err(expected));
+        }
+    }
+}
@@ -5,27 +5,82 @@
 //!
 //! # Key Functions
 //!
-//! - **Discriminant Generation**: Creates cryptographically secure negative discriminants
-//!   that satisfy the mathematical requirements for class group operations
+//! - **Discriminant Generation**: Creates negative prime discriminants Δ = -p with
+//!   p ≡ 7 (mod 8), compatible with Chia's `create_discriminant`
 //! - **Prime Generation**: Uses Fiat-Shamir heuristic to generate challenge primes
 //!   for the non-interactive proof system
-//! - **Primality Testing**: Miller-Rabin probabilistic primality test for efficiency
+//! - **Hash to Group**: Maps challenges to forms of a fixed public discriminant
+//! - **Primality Testing**: Baillie–PSW test with trial division, and optional extra
+//!   Miller-Rabin rounds with random bases
+//! - **Modular Arithmetic**: Montgomery exponentiation, Jacobi and Kronecker symbols,
+//!   and square roots modulo primes (Tonelli–Shanks and Cipolla)
 //!
 //! # Security Considerations
 //!
-//! - Discriminants must be ≡ 1 (mod 4) for proper class group structure
+//! - |Δ| is prime, so the class group has no known structure such as a
+//!   computable 2-torsion subgroup from a factorization of Δ
+//! - Δ ≡ 1 (mod 8), so (2, 1, c) is a valid form that can serve as generator
 //! - Challenge primes are generated deterministically from public inputs
 //! - All randomness is derived from cryptographic hash functions
 
-use num_bigint::{BigInt, Sign};
-use num_traits::{Zero, One, Signed};
+use num_bigint::{BigInt, BigUint, RandBigInt, Sign};
+use num_integer::Integer;
+use num_traits::{Zero, One, Signed, ToPrimitive};
+use once_cell::sync::Lazy;
 use sha2::{Sha256, Digest};
 
+use crate::class_group::ClassGroupElement;
+use crate::error::VdfError;
+use crate::params::HashFunction;
+
+/// Product of the smallest primes: 8·3·5·7·11·13
+/// 
+/// Discriminant candidates are drawn from residues modulo M that are ≡ 7 (mod 8)
+/// and coprime to M, so they are never divisible by 2, 3, 5, 7, 11 or 13.
+const M: u32 = 8 * 3 * 5 * 7 * 11 * 13;
+
+/// Number of candidates n + M·i covered by one sieve pass
+const SIEVE_LEN: usize = 1 << 16;
+
+/// Odd primes below 2^16, in increasing order
+pub static SMALL_PRIMES: Lazy<Vec<u32>> = Lazy::new(|| {
+    let mut composite = vec![false; SIEVE_LEN];
+    let mut primes = Vec::new();
+    for i in (3..SIEVE_LEN).step_by(2) {
+        if !composite[i] {
+            primes.push(i as u32);
+            for j in (i * i..SIEVE_LEN).step_by(2 * i) {
+                composite[j] = true;
+            }
+        }
+    }
+    primes
+});
+
+/// Residues r < M with r ≡ 7 (mod 8) and gcd(r, M) = 1
+static RESIDUES: Lazy<Vec<u32>> = Lazy::new(|| {
+    (7..M)
+        .step_by(8)
+        .filter(|r| [3, 5, 7, 11, 13].iter().all(|p| r % p != 0))
+        .collect()
+});
+
+/// Sieve primes p > 13 below 2^16 together with M⁻¹ mod p
+static SIEVE_INFO: Lazy<Vec<(u32, u32)>> = Lazy::new(|| {
+    SMALL_PRIMES
+        .iter()
+        .skip(5)
+        .map(|&p| {
+            let inverse = mod_pow(&BigInt::from(M % p), &BigInt::from(p - 2), &BigInt::from(p));
+            (p, inverse.to_u32().expect("inverse is below p"))
+        })
+        .collect()
+});
+
 /// Generate a cryptographically secure discriminant from challenge
 /// 
-/// Following the approach used in POA Networks VDF implementation,
-/// this generates a negative discriminant of specified bit length that
-/// satisfies the requirement D ≡ 1 (mod 4) for proper class group structure.
+/// This is `create_discriminant` with the challenge as seed, so Δ = -p for a
+/// prime p ≡ 7 (mod 8) and the generator (2, 1, c) is a valid form.
 /// 
 /// # Arguments
 /// * `challenge` - The input challenge bytes
@@ -34,75 +89,81 @@ use sha2::{Sha256, Digest};
 /// # Returns
 /// A negative BigInt discriminant suitable for class group operations
 pub fn generate_discriminant(challenge: &[u8], bit_length: usize) -> BigInt {
-    let mut hasher = Sha256::new();
-    hasher.update(challenge);
-    hasher.update(b"wesolowski_discriminant");
+    create_discriminant(challenge, bit_length)
+}
+
+/// Create a prime discriminant from a seed, compatible with Chia's `create_discriminant`
+/// 
+/// The seed is expanded with SHA-256 into a random `length`-bit number n with the
+/// top bit set, which is moved to a random residue class modulo M that is ≡ 7
+/// (mod 8). The result is -q for the smallest probable prime q = n + M·i, found
+/// by sieving blocks of 2^16 candidates with the odd primes below 2^16.
+/// 
+/// The output is bit-for-bit identical to the reference implementation of the
+/// Chia VDF competition and the POA Network `vdf` crate for the same seed and
+/// length.
+/// 
+/// # Arguments
+/// * `seed` - The seed bytes
+/// * `length` - Bit length of the discriminant, at least 8
+/// 
+/// # Returns
+/// Δ = -q with q prime, q ≡ 7 (mod 8), and q of `length` bits except with
+/// negligible probability
+pub fn create_discriminant(seed: &[u8], length: usize) -> BigInt {
+    assert!(length >= 8, "discriminant length must be at least 8 bits");
     
-    let mut discriminant;
-    let mut counter = 0u64;
+    // `length` bits of entropy for n, then 2 more bytes selecting the residue
+    let byte_count = length.div_ceil(8) + 2;
+    let entropy = entropy_from_seed(seed, byte_count);
+    let (body, tail) = entropy.split_at(byte_count - 2);
+    let mut n = BigInt::from_bytes_be(Sign::Plus, body) >> ((8 - (length & 7)) & 7);
+    n.set_bit(length as u64 - 1, true);
+    
+    let selector = (usize::from(tail[0]) << 8) | usize::from(tail[1]);
+    let residue = RESIDUES[selector % RESIDUES.len()];
+    n -= &n % M;
+    n += residue;
     
-    // Generate a negative discriminant of specified bit length
-    // Must be ≡ 1 (mod 4) for proper class group structure
     loop {
-        let mut hasher = Sha256::new();
-        hasher.update(challenge);
-        hasher.update(b"discriminant_generation");
-        hasher.update(counter.to_be_bytes());
-        let hash = hasher.finalize();
-        
-        // Create discriminant from hash
-        discriminant = BigInt::from_bytes_be(Sign::Plus, &hash);
-        
-        // Ensure proper bit length by setting the most significant bit
-        if bit_length > 256 {
-            // For larger bit lengths, extend with additional hashing
-            let mut extended_bytes = Vec::new();
-            let mut hash_counter = 0u32;
-            
-            while extended_bytes.len() * 8 < bit_length {
-                let mut ext_hasher = Sha256::new();
-                ext_hasher.update(hash);
-                ext_hasher.update(hash_counter.to_be_bytes());
-                let ext_hash = ext_hasher.finalize();
-                extended_bytes.extend_from_slice(&ext_hash);
-                hash_counter += 1;
+        // Mark the i with p | n + M·i, i.e. i ≡ -n·M⁻¹ (mod p)
+        let mut composite = vec![false; SIEVE_LEN];
+        for &(p, inverse) in SIEVE_INFO.iter() {
+            let n_mod_p = (&n % p).to_u64().expect("remainder is below p");
+            let mut i = ((p as u64 - n_mod_p) * inverse as u64 % p as u64) as usize;
+            while i < SIEVE_LEN {
+                composite[i] = true;
+                i += p as usize;
             }
-            
-            // Truncate to desired bit length
-            let byte_length = bit_length.div_ceil(8);
-            extended_bytes.truncate(byte_length);
-            discriminant = BigInt::from_bytes_be(Sign::Plus, &extended_bytes);
-        }
-        
-        // Set the discriminant to be negative
-        discriminant = -discriminant.abs();
-        
-        // Ensure discriminant ≡ 1 (mod 4) for proper class group
-        let remainder = &discriminant % 4;
-        if remainder != BigInt::from(-3) {
-            discriminant -= &remainder + 3;
         }
         
-        // Check if discriminant has the correct bit length
-        let actual_bits = discriminant.bits() as usize;
-        if actual_bits >= bit_length - 8 && actual_bits <= bit_length + 8 {
-            break;
+        for (i, _) in composite.iter().enumerate().filter(|(_, composite)| !**composite) {
+            let candidate = &n + M as u64 * i as u64;
+            if is_probably_prime(&candidate) {
+                return -candidate;
+            }
         }
         
+        n += BigInt::from(M) << 16;
+    }
+}
+
+/// Expand a seed to `byte_count` bytes as SHA-256(seed || counter) blocks
+/// 
+/// The counter is 2 bytes, big-endian, starting at 0.
+fn entropy_from_seed(seed: &[u8], byte_count: usize) -> Vec<u8> {
+    assert!(byte_count <= 32 * 0xFFFF, "seed expansion is limited to 65535 blocks");
+    let mut entropy = Vec::with_capacity(byte_count);
+    let mut counter = 0u16;
+    while entropy.len() < byte_count {
+        let mut hasher = Sha256::new();
+        hasher.update(seed);
+        hasher.update(counter.to_be_bytes());
+        entropy.extend_from_slice(&hasher.finalize());
         counter += 1;
-        if counter > 10000 {
-            // Fallback discriminant with correct properties
-            discriminant = -(BigInt::one() << (bit_length - 1)) - BigInt::from(3);
-            // Ensure ≡ 1 (mod 4)
-            let remainder = &discriminant % 4;
-            if remainder != BigInt::from(-3) {
-                discriminant -= &remainder + 3;
-            }
-            break;
-        }
     }
-    
-    discriminant
+    entropy.truncate(byte_count);
+    entropy
 }
 
 /// Hash function to generate prime for Fiat-Shamir transform
@@ -110,6 +171,11 @@ pub fn generate_discriminant(challenge: &[u8], bit_length: usize) -> BigInt {
 /// This function takes multiple byte arrays and produces a prime number
 /// using SHA-256 hashing followed by primality testing.
 /// 
+/// The prime is the first one above the hash, so it is biased towards primes
+/// after large gaps and has at most 256 bits. It derives the challenge of
+/// version 2 proofs and is kept for verifying them; new code should use
+/// `hash_to_prime`.
+/// 
 /// # Arguments
 /// * `data` - Array of byte slices to hash together
 /// 
@@ -129,7 +195,7 @@ pub fn hash_prime(data: &[&[u8]]) -> BigInt {
         prime += 1;
     }
     
-    // Simple primality check (for demo purposes)
+    // Next probable prime, Baillie–PSW
     while !is_probably_prime(&prime) {
         prime += 2;
     }
@@ -137,11 +203,160 @@ pub fn hash_prime(data: &[&[u8]]) -> BigInt {
     prime
 }
 
-/// Simple Miller-Rabin primality test
+/// Default domain separator of `hash_to_prime`
+pub const HASH_TO_PRIME_DOMAIN: &[u8] = b"wesolowski_vdf/hash_to_prime";
+
+/// Number of small primes in the sieve of `hash_to_prime`
+const HASH_TO_PRIME_SIEVE: usize = 512;
+
+/// Sieve primes of `hash_to_prime`, grouped so that each group's product fits in a u64
+/// 
+/// A candidate is reduced once modulo each product, and the small remainders are
+/// then checked against the primes of the group.
+static SIEVE_GROUPS: Lazy<Vec<(u64, Vec<u32>)>> = Lazy::new(|| {
+    let mut groups: Vec<(u64, Vec<u32>)> = Vec::new();
+    for &p in SMALL_PRIMES.iter().take(HASH_TO_PRIME_SIEVE) {
+        match groups.last_mut() {
+            Some((product, primes)) if product.checked_mul(p as u64).is_some() => {
+                *product *= p as u64;
+                primes.push(p);
+            }
+            _ => groups.push((p as u64, vec![p])),
+        }
+    }
+    groups
+});
+
+/// Hash a seed to a uniformly distributed prime of a chosen bit length
+/// 
+/// Candidates are derived from the domain separator, the seed and a counter, and
+/// have the top and bottom bits set. A candidate with a factor among the first
+/// 512 odd primes is rejected by the sieve, otherwise it is tested with
+/// Baillie–PSW; a rejected candidate is replaced by hashing with the next
+/// counter value. Since every candidate is an independent, uniform odd number of
+/// `bits` bits, the result is uniform among primes of that size, rather than
+/// favouring primes that follow large gaps as searching upwards would. The
+/// expected number of candidates is about 0.35·`bits`, and the sieve rejects
+/// most of them for the cost of a few divisions each.
+/// 
+/// Candidate k is expanded from SHA-256 or SHA-512 blocks
+/// H(u64 len ‖ domain ‖ u64 len ‖ seed ‖ u32 k ‖ u32 block), truncated to `bits`.
+/// 
+/// # Arguments
+/// * `hash` - The hash function used for the expansion
+/// * `domain` - Domain separator, such as `HASH_TO_PRIME_DOMAIN`
+/// * `seed` - The bytes to derive the prime from
+/// * `bits` - The bit length of the prime, at least 2
+/// 
+/// # Returns
+/// A probable prime of exactly `bits` bits
+pub fn hash_to_prime(hash: HashFunction, domain: &[u8], seed: &[u8], bits: usize) -> BigInt {
+    assert!(bits >= 2, "prime must have at least 2 bits");
+    let len = bits.div_ceil(8);
+    
+    let mut prefix = Vec::with_capacity(16 + domain.len() + seed.len());
+    prefix.extend_from_slice(&(domain.len() as u64).to_be_bytes());
+    prefix.extend_from_slice(domain);
+    prefix.extend_from_slice(&(seed.len() as u64).to_be_bytes());
+    prefix.extend_from_slice(seed);
+    
+    for counter in 0u32.. {
+        let mut bytes = Vec::with_capacity(len);
+        let mut block = 0u32;
+        while bytes.len() < len {
+            let mut input = prefix.clone();
+            input.extend_from_slice(&counter.to_be_bytes());
+            input.extend_from_slice(&block.to_be_bytes());
+            bytes.extend_from_slice(&hash.digest(&input));
+            block += 1;
+        }
+        bytes.truncate(len);
+        
+        let mut candidate = BigInt::from_bytes_be(Sign::Plus, &bytes) >> (len * 8 - bits);
+        candidate.set_bit(bits as u64 - 1, true);
+        candidate.set_bit(0, true);
+        
+        if passes_sieve(&candidate) && is_probably_prime(&candidate) {
+            return candidate;
+        }
+    }
+    unreachable!("no prime among 2^32 candidates")
+}
+
+/// Whether n has no factor among the sieve primes, other than n itself
+fn passes_sieve(n: &BigInt) -> bool {
+    SIEVE_GROUPS.iter().all(|(product, primes)| {
+        let remainder = (n % *product).to_u64().expect("remainder is below the product");
+        primes.iter().all(|&p| !remainder.is_multiple_of(p as u64) || n == &BigInt::from(p))
+    })
+}
+
+/// Domain separator for hashing challenges to class group elements
+const HASH_TO_GROUP_DOMAIN: &[u8] = b"wesolowski_vdf/hash_to_group";
+
+/// Bit length of the prime leading coefficient chosen by `hash_to_group`
+const HASH_TO_GROUP_BITS: usize = 256;
+
+/// Map a challenge deterministically to an element of the class group of Δ
+/// 
+/// This lets many challenges share one long-lived public discriminant: instead of
+/// deriving a new group per input, the input selects a form in a fixed group.
+/// A hash of the challenge and a counter picks a prime p ≡ 3 (mod 4) for which
+/// Δ is a square mod p, and the result is `ClassGroupElement::prime_form` of p:
+/// b = Δ^((p+1)/4) mod p is a square root of Δ, taken odd so that 4p divides
+/// b² - Δ, and the form (p, b, (b² - Δ)/4p) is reduced.
+/// 
+/// # Arguments
+/// * `discriminant` - A negative discriminant with Δ ≡ 1 (mod 4)
+/// * `challenge` - The input bytes to map
+/// 
+/// # Returns
+/// A reduced form of discriminant Δ, or `VdfError::InvalidDiscriminant` if Δ is
+/// not negative or not ≡ 1 (mod 4)
+pub fn hash_to_group(discriminant: &BigInt, challenge: &[u8]) -> Result<ClassGroupElement, VdfError> {
+    if !discriminant.is_negative() {
+        return Err(VdfError::InvalidDiscriminant("discriminant must be negative"));
+    }
+    if !discriminant.mod_floor(&BigInt::from(4)).is_one() {
+        return Err(VdfError::InvalidDiscriminant("discriminant must be ≡ 1 (mod 4)"));
+    }
+    
+    let mut counter = 0u64;
+    loop {
+        let mut bytes = Vec::with_capacity(HASH_TO_GROUP_BITS / 8);
+        let mut block = 0u32;
+        while bytes.len() < HASH_TO_GROUP_BITS / 8 {
+            let mut hasher = Sha256::new();
+            hasher.update(HASH_TO_GROUP_DOMAIN);
+            hasher.update((challenge.len() as u64).to_be_bytes());
+            hasher.update(challenge);
+            hasher.update(counter.to_be_bytes());
+            hasher.update(block.to_be_bytes());
+            bytes.extend_from_slice(&hasher.finalize());
+            block += 1;
+        }
+        bytes.truncate(HASH_TO_GROUP_BITS / 8);
+        counter += 1;
+        
+        // Top bit set for a fixed size, low bits 11 for p ≡ 3 (mod 4)
+        bytes[0] |= 0x80;
+        *bytes.last_mut().expect("non-empty") |= 0x03;
+        let p = BigInt::from_bytes_be(Sign::Plus, &bytes);
+        if let Some(form) = ClassGroupElement::prime_form(discriminant.clone(), &p) {
+            return Ok(form);
+        }
+    }
+}
+
+/// Number of small primes tried by trial division before the probabilistic tests
+const TRIAL_DIVISION_PRIMES: usize = 256;
+
+/// Baillie–PSW probable prime test
 /// 
-/// This implements a probabilistic primality test using the Miller-Rabin algorithm
-/// with a fixed set of small witnesses. For cryptographic applications, this should
-/// be replaced with a more robust implementation.
+/// Trial division by the smallest odd primes, then a strong Miller–Rabin test to
+/// base 2 and a strong Lucas test with Selfridge's parameters. No composite is
+/// known to pass both tests, and the bases are not fixed values an adversary
+/// could search pseudoprimes for, unlike Miller–Rabin with a set of small bases.
 /// 
 /// # Arguments
 /// * `n` - The number to test for primality
@@ -152,80 +367,813 @@ pub fn is_probably_prime(n: &BigInt) -> bool {
     if n < &BigInt::from(2) {
         return false;
     }
-    if n == &BigInt::from(2) || n == &BigInt::from(3) {
+    if n.is_even() {
+        return n == &BigInt::from(2);
+    }
+    
+    for &p in SMALL_PRIMES.iter().take(TRIAL_DIVISION_PRIMES) {
+        if (n % p).is_zero() {
+            return n == &BigInt::from(p);
+        }
+    }
+    // Below the square of the last trial divisor, trial division is conclusive
+    let limit = SMALL_PRIMES[TRIAL_DIVISION_PRIMES - 1] as u64;
+    if n.to_u64().is_some_and(|n| n < limit * limit) {
         return true;
     }
-    if n % 2 == BigInt::zero() {
+    
+    miller_rabin(n, &BigInt::from(2)) && strong_lucas(n)
+}
+
+/// Baillie–PSW test followed by `rounds` Miller–Rabin tests with random bases
+/// 
+/// Each extra round lets a composite pass with probability at most 1/4,
+/// independently of how n was chosen.
+pub fn is_probably_prime_with_rounds(n: &BigInt, rounds: usize) -> bool {
+    if !is_probably_prime(n) {
         return false;
     }
+    if n < &BigInt::from(5) {
+        return true;
+    }
     
-    // Miller-Rabin with a few small witnesses
-    let witnesses = [2, 3, 5, 7, 11, 13, 17, 19, 23];
+    let mut rng = rand::thread_rng();
+    let high = n - 1;
+    (0..rounds).all(|_| miller_rabin(n, &rng.gen_bigint_range(&BigInt::from(2), &high)))
+}
+
+/// Strong probable prime test of an odd n > 2 to the given base
+pub fn miller_rabin(n: &BigInt, base: &BigInt) -> bool {
+    let n_minus_1: BigInt = n - 1;
+    let r = n_minus_1.trailing_zeros().unwrap_or(0);
+    let d = &n_minus_1 >> r;
     
-    for &a in &witnesses {
-        if n <= &BigInt::from(a) {
-            return n == &BigInt::from(a);
+    let mut x = mod_pow(base, &d, n);
+    if x.is_one() || x == n_minus_1 {
+        return true;
+    }
+    for _ in 1..r {
+        x = (&x * &x) % n;
+        if x == n_minus_1 {
+            return true;
         }
-        
-        let n_minus_1: BigInt = n - 1;
-        let mut d = n_minus_1.clone();
-        let mut r = 0;
-        
-        while &d % 2 == BigInt::zero() {
-            d >>= 1;
-            r += 1;
+    }
+    false
+}
+
+/// Strong Lucas probable prime test of an odd n > 2
+/// 
+/// Uses Selfridge's method A: D is the first of 5, -7, 9, -11, ... with Jacobi
+/// symbol (D/n) = -1, P = 1 and Q = (1 - D)/4.
+pub fn strong_lucas(n: &BigInt) -> bool {
+    // A square has no D with (D/n) = -1
+    if n.sqrt().pow(2) == *n {
+        return false;
+    }
+    
+    let mut d = BigInt::from(5);
+    loop {
+        match jacobi(&d, n) {
+            -1 => break,
+            0 if d.abs() != *n => return false,
+            _ => {}
         }
-        
-        let mut x = mod_pow(&BigInt::from(a), &d, n);
-        
-        if x == BigInt::one() || x == n_minus_1 {
-            continue;
+        d = if d.is_positive() { -(d + 2u32) } else { -d + 2u32 };
+    }
+    let q: BigInt = (BigInt::one() - &d) / 4;
+    
+    // n + 1 = k·2^s with k odd
+    let n_plus_1: BigInt = n + 1;
+    let s = n_plus_1.trailing_zeros().unwrap_or(0);
+    let k = &n_plus_1 >> s;
+    
+    let half = |x: BigInt| if x.is_odd() { (x + n) >> 1 } else { x >> 1 };
+    let d = d.mod_floor(n);
+    let q = q.mod_floor(n);
+    
+    // Left-to-right ladder for U_k, V_k and Q^k, with P = 1
+    let mut u = BigInt::one();
+    let mut v = BigInt::one();
+    let mut q_k = q.clone();
+    for i in (0..k.bits() - 1).rev() {
+        u = (&u * &v) % n;
+        v = (&v * &v - (&q_k << 1u32)).mod_floor(n);
+        q_k = (&q_k * &q_k) % n;
+        if k.bit(i) {
+            let next_u = half(&u + &v) % n;
+            v = half(&d * &u + &v) % n;
+            u = next_u;
+            q_k = (&q_k * &q) % n;
         }
-        
-        let mut composite = true;
-        for _ in 0..r-1 {
-            x = mod_pow(&x, &BigInt::from(2), n);
-            if x == n_minus_1 {
-                composite = false;
-                break;
-            }
+    }
+    
+    if u.is_zero() || v.is_zero() {
+        return true;
+    }
+    for _ in 1..s {
+        v = (&v * &v - (&q_k << 1u32)).mod_floor(n);
+        if v.is_zero() {
+            return true;
+        }
+        q_k = (&q_k * &q_k) % n;
+    }
+    false
+}
+
+/// Jacobi symbol (a/n) for odd positive n
+/// 
+/// # Returns
+/// 1 or -1, or 0 if gcd(a, n) > 1
+/// 
+/// # Panics
+/// Panics if n is not odd and positive.
+pub fn jacobi(a: &BigInt, n: &BigInt) -> i32 {
+    assert!(n.is_positive() && n.is_odd(), "Jacobi symbol needs an odd positive modulus");
+    let mut a = a.mod_floor(n);
+    let mut n = n.clone();
+    let mut result = 1;
+    
+    while !a.is_zero() {
+        let twos = a.trailing_zeros().unwrap_or(0);
+        a >>= twos;
+        // (2/n) = -1 exactly when n ≡ 3, 5 (mod 8)
+        let n_mod_8 = (&n % 8u32).to_u32().expect("remainder is below 8");
+        if twos % 2 == 1 && (n_mod_8 == 3 || n_mod_8 == 5) {
+            result = -result;
+        }
+        // Quadratic reciprocity
+        if (&a % 4u32) == BigInt::from(3) && n_mod_8 % 4 == 3 {
+            result = -result;
+        }
+        std::mem::swap(&mut a, &mut n);
+        a %= &n;
+    }
+    
+    if n.is_one() { result } else { 0 }
+}
+
+/// Kronecker symbol (a/n) for any integer n
+/// 
+/// Extends the Jacobi symbol to even and negative n with (a/2) = 0 for even a,
+/// 1 for a ≡ ±1 (mod 8) and -1 for a ≡ ±3 (mod 8), and (a/-1) = -1 for a < 0.
+/// (Δ/p) = 1 exactly when a form (p, b, c) of discriminant Δ exists for a prime p
+/// not dividing Δ, including p = 2.
+pub fn kronecker(a: &BigInt, n: &BigInt) -> i32 {
+    if n.is_zero() {
+        return if a.abs().is_one() { 1 } else { 0 };
+    }
+    
+    let mut result = 1;
+    if n.is_negative() && a.is_negative() {
+        result = -result;
+    }
+    let n = n.abs();
+    
+    let twos = n.trailing_zeros().unwrap_or(0);
+    if twos > 0 {
+        if a.is_even() {
+            return 0;
+        }
+        let a_mod_8 = a.mod_floor(&BigInt::from(8)).to_u32().expect("remainder is below 8");
+        if twos % 2 == 1 && (a_mod_8 == 3 || a_mod_8 == 5) {
+            result = -result;
+        }
+    }
+    
+    result * jacobi(a, &(n >> twos))
+}
+
+/// Square root of a modulo an odd prime p
+/// 
+/// Uses the closed form a^((p+1)/4) for p ≡ 3 (mod 4), Tonelli–Shanks when p - 1
+/// has a small power of two, and Cipolla otherwise, whose cost does not depend on
+/// the power of two.
+/// 
+/// # Returns
+/// The smaller of the two roots r and p - r, or `None` if a is not a square mod p
+pub fn sqrt_mod_prime(a: &BigInt, p: &BigInt) -> Option<BigInt> {
+    let a = a.mod_floor(p);
+    if a.is_zero() {
+        return Some(a);
+    }
+    if jacobi(&a, p) != 1 {
+        return None;
+    }
+    
+    let root = if (p % 4u32) == BigInt::from(3) {
+        mod_pow(&a, &((p + 1u32) >> 2), p)
+    } else if (p - 1u32).trailing_zeros().unwrap_or(0) <= 8 {
+        tonelli_shanks(&a, p)?
+    } else {
+        cipolla(&a, p)?
+    };
+    
+    let other = p - &root;
+    Some(root.min(other))
+}
+
+/// Square root of a modulo an odd prime p by the Tonelli–Shanks algorithm
+/// 
+/// Takes O(s²) multiplications beyond one exponentiation, where 2^s is the
+/// largest power of two dividing p - 1.
+/// 
+/// # Returns
+/// A root r with r² ≡ a (mod p), or `None` if a is not a square mod p
+pub fn tonelli_shanks(a: &BigInt, p: &BigInt) -> Option<BigInt> {
+    let a = a.mod_floor(p);
+    if a.is_zero() {
+        return Some(a);
+    }
+    if jacobi(&a, p) != 1 {
+        return None;
+    }
+    
+    // p - 1 = q·2^s with q odd
+    let p_minus_1: BigInt = p - 1u32;
+    let s = p_minus_1.trailing_zeros().unwrap_or(0);
+    let q = &p_minus_1 >> s;
+    
+    let mut z = BigInt::from(2);
+    while jacobi(&z, p) != -1 {
+        z += 1u32;
+    }
+    
+    let mut m = s;
+    let mut c = mod_pow(&z, &q, p);
+    let mut t = mod_pow(&a, &q, p);
+    let mut r = mod_pow(&a, &((&q + 1u32) >> 1), p);
+    
+    while !t.is_one() {
+        // Least i with t^(2^i) = 1
+        let mut i = 0;
+        let mut t_power = t.clone();
+        while !t_power.is_one() {
+            t_power = (&t_power * &t_power) % p;
+            i += 1;
         }
         
-        if composite {
-            return false;
+        let mut b = c;
+        for _ in 0..m - i - 1 {
+            b = (&b * &b) % p;
         }
+        m = i;
+        c = (&b * &b) % p;
+        t = (&t * &c) % p;
+        r = (&r * &b) % p;
     }
     
-    true
+    Some(r)
 }
 
-/// Modular exponentiation using binary exponentiation
+/// Square root of a modulo an odd prime p by Cipolla's algorithm
 /// 
-/// Computes (base^exp) mod modulus efficiently using the square-and-multiply algorithm.
+/// Finds w with w² - a a non-residue and computes (w + √(w² - a))^((p+1)/2) in
+/// GF(p²), which is one exponentiation regardless of the factorization of p - 1.
+/// 
+/// # Returns
+/// A root r with r² ≡ a (mod p), or `None` if a is not a square mod p
+pub fn cipolla(a: &BigInt, p: &BigInt) -> Option<BigInt> {
+    let a = a.mod_floor(p);
+    if a.is_zero() {
+        return Some(a);
+    }
+    if jacobi(&a, p) != 1 {
+        return None;
+    }
+    
+    let mut w = BigInt::one();
+    let omega = loop {
+        let candidate = (&w * &w - &a).mod_floor(p);
+        if jacobi(&candidate, p) == -1 {
+            break candidate;
+        }
+        w += 1u32;
+    };
+    
+    // (x0 + x1·√ω)(y0 + y1·√ω) = (x0·y0 + x1·y1·ω) + (x0·y1 + x1·y0)·√ω
+    let multiply = |x: &(BigInt, BigInt), y: &(BigInt, BigInt)| {
+        (
+            (&x.0 * &y.0 + &x.1 * &y.1 % p * &omega) % p,
+            (&x.0 * &y.1 + &x.1 * &y.0) % p,
+        )
+    };
+    
+    let exp: BigInt = (p + 1u32) >> 1;
+    let mut result = (BigInt::one(), BigInt::zero());
+    let base = (w, BigInt::one());
+    for i in (0..exp.bits()).rev() {
+        result = multiply(&result, &result);
+        if exp.bit(i) {
+            result = multiply(&result, &base);
+        }
+    }
+    
+    Some(result.0)
+}
+
+/// Modular exponentiation
+/// 
+/// Computes (base^exp) mod modulus. Odd moduli use Montgomery multiplication, see
+/// `Montgomery`; even moduli fall back to square-and-multiply with `%`.
 /// 
 /// # Arguments
 /// * `base` - The base number
-/// * `exp` - The exponent
-/// * `modulus` - The modulus
+/// * `exp` - The exponent, non-negative
+/// * `modulus` - The modulus, positive
 /// 
 /// # Returns
-/// The result of (base^exp) mod modulus
+/// The result of (base^exp) mod modulus, in [0, modulus)
 pub fn mod_pow(base: &BigInt, exp: &BigInt, modulus: &BigInt) -> BigInt {
-    if exp.is_zero() {
-        return BigInt::one();
-    }
+    assert!(!exp.is_negative(), "exponent must be non-negative");
+    assert!(modulus.is_positive(), "modulus must be positive");
     
-    let mut result = BigInt::one();
-    let mut base = base % modulus;
-    let mut exp = exp.clone();
+    if let Some(montgomery) = Montgomery::new(modulus) {
+        return montgomery.pow(base, exp);
+    }
     
-    while !exp.is_zero() {
-        if &exp % 2 == BigInt::one() {
+    let base = base.mod_floor(modulus);
+    let mut result = BigInt::one() % modulus;
+    for i in (0..exp.bits()).rev() {
+        result = (&result * &result) % modulus;
+        if exp.bit(i) {
             result = (result * &base) % modulus;
         }
-        base = (&base * &base) % modulus;
-        exp >>= 1;
     }
-    
     result
-}
\ No newline at end of file
+}
+
+/// Montgomery arithmetic modulo a fixed odd modulus n > 1
+/// 
+/// Residues are kept as x·R mod n with R = 2^(64·k) for a k-limb modulus, so a
+/// product is reduced by the CIOS (coarsely integrated operand scanning) method
+/// with word multiplications and shifts instead of a division. Worth it when many
+/// multiplications share one modulus, as in an exponentiation.
+#[derive(Clone, Debug)]
+pub struct Montgomery {
+    modulus: BigInt,
+    /// Limbs of n, least significant first
+    limbs: Vec<u64>,
+    /// -n⁻¹ mod 2^64
+    n_prime: u64,
+    /// R² mod n, for converting into Montgomery form
+    r_squared: Vec<u64>,
+}
+
+impl Montgomery {
+    /// Prepare Montgomery arithmetic modulo n, which must be odd and greater than 1
+    pub fn new(modulus: &BigInt) -> Option<Self> {
+        if !modulus.is_odd() || modulus <= &BigInt::one() {
+            return None;
+        }
+        let limbs = modulus.magnitude().to_u64_digits();
+        
+        // Newton iteration for n⁻¹ mod 2^64; each step doubles the correct bits
+        let mut inverse = 1u64;
+        for _ in 0..6 {
+            inverse = inverse.wrapping_mul(2u64.wrapping_sub(limbs[0].wrapping_mul(inverse)));
+        }
+        
+        let r_squared = (BigInt::one() << (128 * limbs.len())) % modulus;
+        Some(Self {
+            modulus: modulus.clone(),
+            r_squared: to_limbs(&r_squared, limbs.len()),
+            n_prime: inverse.wrapping_neg(),
+            limbs,
+        })
+    }
+
+    /// The modulus n
+    pub fn modulus(&self) -> &BigInt {
+        &self.modulus
+    }
+
+    /// Product a·b mod n
+    pub fn mul(&self, a: &BigInt, b: &BigInt) -> BigInt {
+        let a = self.enter(a);
+        let b = self.enter(b);
+        self.leave(&self.multiply(&a, &b))
+    }
+
+    /// Power base^exp mod n with a 4-bit fixed window
+    pub fn pow(&self, base: &BigInt, exp: &BigInt) -> BigInt {
+        assert!(!exp.is_negative(), "exponent must be non-negative");
+        
+        // base^0 .. base^15 in Montgomery form
+        let mut table = Vec::with_capacity(16);
+        table.push(self.enter(&BigInt::one()));
+        table.push(self.enter(base));
+        for i in 2..16 {
+            let next = self.multiply(&table[i - 1], &table[1]);
+            table.push(next);
+        }
+        
+        let mut result = table[0].clone();
+        let windows = exp.bits().div_ceil(4);
+        for window in (0..windows).rev() {
+            for _ in 0..4 {
+                result = self.multiply(&result, &result);
+            }
+            let digit = (0..4).fold(0, |digit, bit| digit | (usize::from(exp.bit(4 * window + bit)) << bit));
+            if digit != 0 {
+                result = self.multiply(&result, &table[digit]);
+            }
+        }
+        
+        self.leave(&result)
+    }
+
+    /// Montgomery form x·R mod n
+    fn enter(&self, x: &BigInt) -> Vec<u64> {
+        let x = to_limbs(&x.mod_floor(&self.modulus), self.limbs.len());
+        self.multiply(&x, &self.r_squared)
+    }
+
+    /// Ordinary residue x·R⁻¹ mod n of a Montgomery form
+    fn leave(&self, x: &[u64]) -> BigInt {
+        let mut one = vec![0u64; self.limbs.len()];
+        one[0] = 1;
+        from_limbs(&self.multiply(x, &one))
+    }
+
+    /// Montgomery product a·b·R⁻¹ mod n of two reduced residues, by CIOS
+    fn multiply(&self, a: &[u64], b: &[u64]) -> Vec<u64> {
+        let n = &self.limbs;
+        let k = n.len();
+        let mut t = vec![0u64; k + 2];
+        
+        for &b_i in b {
+            // t += a·b_i
+            let mut carry = 0u128;
+            for j in 0..k {
+                let sum = t[j] as u128 + a[j] as u128 * b_i as u128 + carry;
+                t[j] = sum as u64;
+                carry = sum >> 64;
+            }
+            let sum = t[k] as u128 + carry;
+            t[k] = sum as u64;
+            t[k + 1] = (sum >> 64) as u64;
+            
+            // t = (t + m·n) / 2^64, with m chosen so that the division is exact
+            let m = t[0].wrapping_mul(self.n_prime);
+            let mut carry = (t[0] as u128 + m as u128 * n[0] as u128) >> 64;
+            for j in 1..k {
+                let sum = t[j] as u128 + m as u128 * n[j] as u128 + carry;
+                t[j - 1] = sum as u64;
+                carry = sum >> 64;
+            }
+            let sum = t[k] as u128 + carry;
+            t[k - 1] = sum as u64;
+            t[k] = t[k + 1] + (sum >> 64) as u64;
+        }
+        
+        // t < 2n, so one subtraction reduces it
+        if t[k] != 0 || !limbs_less(&t[..k], n) {
+            let mut borrow = 0u64;
+            for j in 0..k {
+                let (difference, under) = t[j].overflowing_sub(n[j]);
+                let (difference, under_borrow) = difference.overflowing_sub(borrow);
+                t[j] = difference;
+                borrow = u64::from(under || under_borrow);
+            }
+        }
+        t.truncate(k);
+        t
+    }
+}
+
+/// Limbs of a non-negative x, least significant first, padded to `len`
+fn to_limbs(x: &BigInt, len: usize) -> Vec<u64> {
+    let mut limbs = x.magnitude().to_u64_digits();
+    limbs.resize(len, 0);
+    limbs
+}
+
+fn from_limbs(limbs: &[u64]) -> BigInt {
+    let digits = limbs.iter().flat_map(|&limb| [limb as u32, (limb >> 32) as u32]).collect();
+    BigInt::from_biguint(Sign::Plus, BigUint::new(digits))
+}
+
+/// Whether a < b for limb vectors of equal length
+fn limbs_less(a: &[u64], b: &[u64]) -> bool {
+    for (x, y) in a.iter().rev().zip(b.iter().rev()) {
+        if x != y {
+            return x < y;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn integer(decimal: &str) -> BigInt {
+        decimal.parse().unwrap()
+    }
+
+    /// Known answers of the Chia VDF reference implementation for the seed 0xaa
+    #[test]
+    fn create_discriminant_matches_chiavdf() {
+        assert_eq!(create_discriminant(b"\xaa", 40), integer("-685537176559"));
+        assert_eq!(
+            create_discriminant(b"\xaa", 1024),
+            integer(concat!(
+                "-11208471744389096429663063172516742066731683613191418514476174383781682509882",
+                "427394963852743081347678693241523614532942268295868231081182819214054220080323",
+                "345750407342623884342617809879459211722505867733607400509994975706778681543998",
+                "2423354682038602405861714139714858603829016409314686266660248501773529803183",
+            ))
+        );
+        for (seed, bits) in [(&b"\xaa"[..], 40), (b"seed", 512)] {
+            let discriminant = create_discriminant(seed, bits);
+            assert_eq!(discriminant.bits(), bits as u64);
+            assert_eq!((-&discriminant).mod_floor(&BigInt::from(8)), BigInt::from(7));
+            assert!(is_probably_prime(&-discriminant));
+        }
+    }
+
+    #[test]
+    fn hash_to_group_returns_reduced_forms_of_the_discriminant() {
+        let discriminant = create_discriminant(b"hash-to-group", 256);
+        let form = hash_to_group(&discriminant, b"challenge").unwrap();
+        assert_eq!(form.discriminant, discriminant);
+        assert!(form.is_valid() && form.is_reduced());
+        assert_eq!(hash_to_group(&discriminant, b"challenge").unwrap(), form);
+        assert_ne!(hash_to_group(&discriminant, b"other").unwrap(), form);
+    }
+
+    #[test]
+    fn hash_to_group_rejects_invalid_discriminants() {
+        for value in [23, -5, -8] {
+            assert!(matches!(
+                hash_to_group(&BigInt::from(value), b"challenge"),
+                Err(VdfError::InvalidDiscriminant(_))
+            ));
+        }
+    }
+
+    /// Primality by trial division, for checking small ranges
+    fn is_prime_reference(n: u64) -> bool {
+        n >= 2 && (2..).take_while(|d| d * d <= n).all(|d| !n.is_multiple_of(d))
+    }
+
+    #[test]
+    fn is_probably_prime_matches_trial_division() {
+        // Below the square of the last trial divisor, and above it where
+        // Miller–Rabin and Lucas decide
+        let limit = SMALL_PRIMES[TRIAL_DIVISION_PRIMES - 1] as u64;
+        for range in [0..20_000, limit * limit - 10_000..limit * limit + 20_000] {
+            for n in range {
+                assert_eq!(is_probably_prime(&BigInt::from(n)), is_prime_reference(n), "{}", n);
+            }
+        }
+    }
+
+    #[test]
+    fn is_probably_prime_accepts_large_primes() {
+        let primes = [
+            (BigInt::one() << 61u32) - 1,
+            (BigInt::one() << 127u32) - 1,
+            (BigInt::one() << 521u32) - 1,
+            integer("170141183460469231731687303715884105757"),
+        ];
+        for p in &primes {
+            assert!(is_probably_prime(p), "{}", p);
+            assert!(is_probably_prime_with_rounds(p, 8), "{}", p);
+        }
+        
+        let composites = [
+            (BigInt::one() << 67u32) - 1,
+            &primes[0] * &primes[1],
+            &primes[1] * &primes[1],
+        ];
+        for n in &composites {
+            assert!(!is_probably_prime(n), "{}", n);
+            assert!(!is_probably_prime_with_rounds(n, 8), "{}", n);
+        }
+    }
+
+    #[test]
+    fn is_probably_prime_rejects_pseudoprimes() {
+        let carmichael = [
+            561u64, 1105, 1729, 2465, 2821, 6601, 8911, 41041, 825265, 321197185, 5394826801,
+            232250619601, 9746347772161,
+        ];
+        for n in carmichael {
+            assert!(!is_probably_prime(&BigInt::from(n)), "{}", n);
+        }
+        
+        // Strong pseudoprimes to base 2; the last two also to all prime bases up to
+        // 31 and 41, so Miller–Rabin with the fixed bases 2..23 accepts them
+        let strong_base_2 = [
+            "2047",
+            "3277",
+            "4033",
+            "4681",
+            "8321",
+            "3215031751",
+            "3825123056546413051",
+            "3317044064679887385961981",
+        ];
+        for n in strong_base_2.map(integer) {
+            assert!(miller_rabin(&n, &BigInt::from(2)), "{}", n);
+            assert!(!is_probably_prime(&n), "{}", n);
+        }
+        let fools_bases_to_31 = integer("3825123056546413051");
+        for base in [2u32, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31] {
+            assert!(miller_rabin(&fools_bases_to_31, &BigInt::from(base)));
+        }
+        
+        // Strong Lucas pseudoprimes, which base 2 rejects
+        for n in [5459u32, 5777, 10877, 16109, 18971, 22499, 24569, 25199, 40309, 58519] {
+            let n = BigInt::from(n);
+            assert!(strong_lucas(&n), "{}", n);
+            assert!(!miller_rabin(&n, &BigInt::from(2)), "{}", n);
+            assert!(!is_probably_prime(&n), "{}", n);
+        }
+    }
+
+    #[test]
+    fn hash_to_prime_returns_primes_of_the_requested_size() {
+        for hash in [HashFunction::Sha256, HashFunction::Sha512] {
+            for bits in [2, 3, 16, 128, 256, 264] {
+                let p = hash_to_prime(hash, HASH_TO_PRIME_DOMAIN, b"seed", bits);
+                assert_eq!(p.bits(), bits as u64, "{:?}, {} bits", hash, bits);
+                assert!(is_probably_prime(&p), "{:?}, {} bits", hash, bits);
+                assert_eq!(hash_to_prime(hash, HASH_TO_PRIME_DOMAIN, b"seed", bits), p);
+            }
+        }
+        assert_eq!(hash_to_prime(HashFunction::Sha256, HASH_TO_PRIME_DOMAIN, b"seed", 2), BigInt::from(3));
+    }
+
+    #[test]
+    fn hash_to_prime_separates_inputs() {
+        let prime = |hash, domain: &[u8], seed: &[u8]| hash_to_prime(hash, domain, seed, 256);
+        let base = prime(HashFunction::Sha256, HASH_TO_PRIME_DOMAIN, b"seed");
+        assert_ne!(prime(HashFunction::Sha512, HASH_TO_PRIME_DOMAIN, b"seed"), base);
+        assert_ne!(prime(HashFunction::Sha256, b"other domain", b"seed"), base);
+        assert_ne!(prime(HashFunction::Sha256, HASH_TO_PRIME_DOMAIN, b"seee"), base);
+        // Lengths are encoded, so moving bytes between domain and seed changes the prime
+        assert_ne!(prime(HashFunction::Sha256, b"ab", b"c"), prime(HashFunction::Sha256, b"a", b"bc"));
+    }
+
+    #[test]
+    fn sieve_keeps_the_sieve_primes_themselves() {
+        for &p in SMALL_PRIMES.iter().take(HASH_TO_PRIME_SIEVE) {
+            assert!(passes_sieve(&BigInt::from(p)));
+            assert!(!passes_sieve(&BigInt::from(p as u64 * 3)));
+        }
+        let p = (BigInt::one() << 127u32) - 1;
+        assert!(passes_sieve(&p));
+        assert!(!passes_sieve(&(&p * 1009u32)));
+    }
+
+    #[test]
+    fn hash_prime_returns_the_next_prime_after_the_hash() {
+        let p = hash_prime(&[b"generator", b"output"]);
+        let hash = BigInt::from_bytes_be(Sign::Plus, &Sha256::new().chain_update(b"generatoroutput").finalize());
+        assert!(is_probably_prime(&p));
+        assert!(p >= hash);
+        let mut candidate = hash | BigInt::one();
+        while candidate < p {
+            assert!(!is_probably_prime(&candidate));
+            candidate += 2;
+        }
+    }
+
+    /// Moduli with one limb, several limbs, all-ones limbs, and even moduli
+    fn moduli() -> Vec<BigInt> {
+        let mut moduli: Vec<BigInt> = [1u64, 2, 3, 4, 97, 1 << 32, u64::MAX, (1 << 63) + 1]
+            .into_iter()
+            .map(BigInt::from)
+            .collect();
+        moduli.push((BigInt::one() << 128u32) - 1);
+        moduli.push((BigInt::one() << 521u32) - 1);
+        moduli.push(BigInt::one() << 200u32);
+        moduli.push(create_discriminant(b"mod-pow", 1024).abs());
+        moduli
+    }
+
+    #[test]
+    fn mod_pow_matches_biguint_modpow() {
+        let mut rng = StdRng::seed_from_u64(19);
+        for modulus in moduli() {
+            let reference = modulus.magnitude();
+            for _ in 0..20 {
+                let base = rng.gen_bigint_range(&(-&modulus * 2), &(&modulus * 2));
+                let exp = rng.gen_bigint_range(&BigInt::zero(), &(BigInt::one() << 300u32));
+                let expected = base.mod_floor(&modulus).magnitude().modpow(exp.magnitude(), reference);
+                assert_eq!(mod_pow(&base, &exp, &modulus), BigInt::from(expected), "{}^{} mod {}", base, exp, modulus);
+            }
+            assert_eq!(mod_pow(&BigInt::from(5), &BigInt::zero(), &modulus), BigInt::one() % &modulus);
+        }
+    }
+
+    #[test]
+    fn montgomery_matches_plain_arithmetic() {
+        let mut rng = StdRng::seed_from_u64(19);
+        for modulus in moduli() {
+            let Some(montgomery) = Montgomery::new(&modulus) else {
+                assert!(modulus.is_even() || modulus.is_one(), "{}", modulus);
+                continue;
+            };
+            for _ in 0..20 {
+                let a = rng.gen_bigint_range(&BigInt::zero(), &modulus);
+                let b = rng.gen_bigint_range(&BigInt::zero(), &modulus);
+                assert_eq!(montgomery.mul(&a, &b), (&a * &b) % &modulus);
+            }
+            let top = &modulus - 1;
+            assert_eq!(montgomery.mul(&top, &top), BigInt::one());
+        }
+    }
+
+    #[test]
+    fn jacobi_matches_euler_criterion() {
+        let mut rng = StdRng::seed_from_u64(19);
+        let primes = [
+            BigInt::from(3),
+            BigInt::from(1009),
+            BigInt::from(998244353),
+            (BigInt::one() << 127u32) - 1,
+            create_discriminant(b"jacobi", 512).abs(),
+        ];
+        for p in &primes {
+            let half: BigInt = (p - 1) >> 1;
+            for _ in 0..50 {
+                let a = rng.gen_bigint_range(&(-p * 3), &(p * 3));
+                let expected = match mod_pow(&a, &half, p) {
+                    x if x.is_zero() => 0,
+                    x if x.is_one() => 1,
+                    _ => -1,
+                };
+                assert_eq!(jacobi(&a, p), expected, "({}/{})", a, p);
+            }
+        }
+        
+        // (a/mn) = (a/m)(a/n) for odd composite moduli
+        for a in -30..30 {
+            let a = BigInt::from(a);
+            for (m, n) in [(3, 5), (7, 7), (9, 11), (1009, 15)] {
+                let (m, n) = (BigInt::from(m), BigInt::from(n));
+                assert_eq!(jacobi(&a, &(&m * &n)), jacobi(&a, &m) * jacobi(&a, &n));
+            }
+        }
+    }
+
+    #[test]
+    fn kronecker_extends_jacobi() {
+        for a in -20i32..20 {
+            let big = BigInt::from(a);
+            let expected_2 = match a.rem_euclid(8) {
+                1 | 7 => 1,
+                3 | 5 => -1,
+                _ => 0,
+            };
+            assert_eq!(kronecker(&big, &BigInt::from(2)), expected_2, "({}/2)", a);
+            assert_eq!(kronecker(&big, &BigInt::from(-1)), if a < 0 { -1 } else { 1 }, "({}/-1)", a);
+            assert_eq!(kronecker(&big, &BigInt::zero()), i32::from(a.abs() == 1), "({}/0)", a);
+            for n in [1, 3, 15, 21] {
+                let n = BigInt::from(n);
+                assert_eq!(kronecker(&big, &n), jacobi(&big, &n));
+                assert_eq!(kronecker(&big, &(&n * 12)), expected_2 * expected_2 * kronecker(&big, &(&n * 3)));
+            }
+        }
+    }
+
+    #[test]
+    fn square_roots_square_back() {
+        let mut rng = StdRng::seed_from_u64(19);
+        // p ≡ 3 (mod 4), p - 1 with a small power of two for Tonelli–Shanks, and
+        // p - 1 with a large power of two for Cipolla
+        let primes = [
+            BigInt::from(1019),
+            (BigInt::one() << 127u32) - 1,
+            create_discriminant(b"sqrt", 512).abs(),
+            BigInt::from(1009),
+            (BigInt::one() << 255u32) - 19,
+            BigInt::from(998244353),
+            BigInt::from(3221225473u64),
+        ];
+        for p in &primes {
+            assert!(is_probably_prime(p), "{}", p);
+            for _ in 0..30 {
+                let a = rng.gen_bigint_range(&BigInt::zero(), p);
+                let square = |r: &BigInt| (r * r).mod_floor(p);
+                if jacobi(&a, p) == 1 {
+                    let root = sqrt_mod_prime(&a, p).unwrap();
+                    assert_eq!(square(&root), a, "√{} mod {}", a, p);
+                    assert!(root <= p - &root);
+                    assert_eq!(square(&tonelli_shanks(&a, p).unwrap()), a);
+                    assert_eq!(square(&cipolla(&a, p).unwrap()), a);
+                } else {
+                    assert_eq!(sqrt_mod_prime(&a, p), None);
+                    assert_eq!(tonelli_shanks(&a, p), None);
+                    assert_eq!(cipolla(&a, p), None);
+                }
+            }
+            assert_eq!(sqrt_mod_prime(&BigInt::zero(), p), Some(BigInt::zero()));
+        }
+    }
+}
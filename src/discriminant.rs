@@ -0,0 +1,156 @@
+//! Validated Class Group Discriminants
+//!
+//! Generating a discriminant means searching for a prime of hundreds of bits,
+//! which costs far more than verifying a proof. Deployments that fix the class
+//! group once, e.g. as a network parameter, should do that search a single time.
+//! A `Discriminant` holds a value that has been checked to define a class group
+//! of positive definite forms, so it can be created once at startup, cached or
+//! shared between threads behind an `Arc`, and passed to
+//! `WesolowskiVDF::from_discriminant` for every instance.
+//!
+//! # Checks
+//!
+//! - **Sign**: Δ < 0, so that the forms are definite
+//! - **Size**: |Δ| has a bit length in `params::DISCRIMINANT_BITS`, the sizes
+//!   `VdfParams` accepts; smaller class groups offer no security
+//! - **Congruence**: Δ ≡ 1 (mod 4), the fundamental odd discriminants used by the VDF
+//! - **Primality** (optional): -Δ is a probable prime, so the class number is odd and
+//!   hard to compute; see `Discriminant::new_prime`
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, Signed};
+use std::fmt;
+
+use crate::crypto::{create_discriminant, is_probably_prime};
+use crate::error::VdfError;
+use crate::params::DISCRIMINANT_BITS;
+
+/// A negative discriminant Δ ≡ 1 (mod 4) of a supported size
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Discriminant(BigInt);
+
+impl Discriminant {
+    /// Check that Δ < 0, Δ ≡ 1 (mod 4) and that |Δ| has 512 to 16384 bits
+    /// 
+    /// Primality of -Δ is not tested; use `new_prime` for values from an
+    /// untrusted source.
+    pub fn new(value: BigInt) -> Result<Self, VdfError> {
+        if !value.is_negative() {
+            return Err(VdfError::InvalidDiscriminant("discriminant must be negative"));
+        }
+        if !DISCRIMINANT_BITS.contains(&(value.bits() as usize)) {
+            return Err(VdfError::InvalidDiscriminant("discriminant size out of range"));
+        }
+        if !value.mod_floor(&BigInt::from(4)).is_one() {
+            return Err(VdfError::InvalidDiscriminant("discriminant must be ≡ 1 (mod 4)"));
+        }
+        Ok(Self(value))
+    }
+
+    /// Check the conditions of `new` and that -Δ is a probable prime
+    /// 
+    /// The primality test is Baillie–PSW, see `crypto::is_probably_prime`.
+    pub fn new_prime(value: BigInt) -> Result<Self, VdfError> {
+        let discriminant = Self::new(value)?;
+        if !discriminant.is_prime() {
+            return Err(VdfError::InvalidDiscriminant("-Δ is not prime"));
+        }
+        Ok(discriminant)
+    }
+
+    /// Generate the prime discriminant of `bits` bits for a seed
+    /// 
+    /// This is `crypto::create_discriminant`, so -Δ is prime and ≡ 7 (mod 8) and
+    /// the generator (2, 1, c) is a valid form.
+    /// 
+    /// Returns `VdfError::InvalidParams` if `bits` is outside
+    /// `params::DISCRIMINANT_BITS`.
+    pub fn from_seed(seed: &[u8], bits: usize) -> Result<Self, VdfError> {
+        if !DISCRIMINANT_BITS.contains(&bits) {
+            return Err(VdfError::InvalidParams("discriminant size out of range"));
+        }
+        Self::new(create_discriminant(seed, bits))
+    }
+
+    /// Whether -Δ is a probable prime
+    pub fn is_prime(&self) -> bool {
+        is_probably_prime(&-&self.0)
+    }
+
+    /// Bit length of |Δ|
+    pub fn bits(&self) -> usize {
+        self.0.bits() as usize
+    }
+
+    /// The discriminant as an integer
+    pub fn as_bigint(&self) -> &BigInt {
+        &self.0
+    }
+
+    /// Consume the discriminant and return the integer
+    pub fn into_bigint(self) -> BigInt {
+        self.0
+    }
+}
+
+impl TryFrom<BigInt> for Discriminant {
+    type Error = VdfError;
+
+    fn try_from(value: BigInt) -> Result<Self, VdfError> {
+        Self::new(value)
+    }
+}
+
+impl From<Discriminant> for BigInt {
+    fn from(discriminant: Discriminant) -> Self {
+        discriminant.0
+    }
+}
+
+impl AsRef<BigInt> for Discriminant {
+    fn as_ref(&self) -> &BigInt {
+        &self.0
+    }
+}
+
+impl fmt::Display for Discriminant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_rejects_invalid_values() {
+        let seeded = Discriminant::from_seed(b"discriminant-test", 512).unwrap();
+        assert_eq!(Discriminant::new(seeded.as_bigint().clone()), Ok(seeded.clone()));
+        assert_eq!(seeded.bits(), 512);
+        assert!(seeded.is_prime());
+        
+        let rejected = [
+            BigInt::from(-3),
+            BigInt::from(-7),
+            -seeded.as_bigint(),
+            seeded.as_bigint() - 2,
+            -(BigInt::one() << 16384usize) - 3,
+        ];
+        for value in rejected {
+            assert!(matches!(Discriminant::new(value), Err(VdfError::InvalidDiscriminant(_))));
+        }
+        assert!(matches!(
+            Discriminant::new_prime(seeded.as_bigint() * 9),
+            Err(VdfError::InvalidDiscriminant("-Δ is not prime"))
+        ));
+    }
+
+    #[test]
+    fn from_seed_rejects_unsupported_sizes() {
+        for bits in [40, 511, 16385] {
+            assert!(matches!(Discriminant::from_seed(b"seed", bits), Err(VdfError::InvalidParams(_))));
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Error Types for VDF Operations
+//!
+//! Fallible operations of the library return `VdfError`, which names the exact
+//! reason an input was rejected. This lets a caller that checks proofs from
+//! untrusted peers log why a proof failed instead of receiving a bare `false`.
+//!
+//! There is no error for an iteration count out of range or for a mismatching
+//! challenge. A proof holds only π: the verifier derives the challenge prime ℓ
+//! from its own inputs and r = 2^t mod ℓ by modular exponentiation, so any t is
+//! checked at the cost of its bit length, and the proof carries no challenge or
+//! quotient that could disagree with the recomputed values. A proof for another
+//! challenge or iteration count fails with `ProofRejected`.
+
+use std::fmt;
+
+use crate::class_group::FormError;
+use crate::proof::ProofVersion;
+
+/// Errors raised while decoding, combining or verifying VDF data
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VdfError {
+    /// The proof bytes do not follow the proof layout
+    MalformedProof(&'static str),
+    /// The proof was encoded with an unknown wire format version
+    UnsupportedVersion(u8),
+    /// The proof is of a known version, but not the one the VDF parameters select
+    VersionMismatch { expected: ProofVersion, found: ProofVersion },
+    /// The bytes are not a valid serialized form
+    MalformedEncoding(&'static str),
+    /// Two forms, or a form and the VDF, use different discriminants
+    DiscriminantMismatch,
+    /// The coefficients do not describe a positive definite form of the discriminant
+    InvalidForm(FormError),
+    /// Input continues after a complete value; holds the number of extra bytes
+    TrailingBytes(usize),
+    /// The proof is well-formed but π^ℓ · g^r differs from the claimed output
+    ProofRejected,
+    /// VDF parameters are out of range or their encoding is invalid
+    InvalidParams(&'static str),
+    /// The discriminant is not negative, not ≡ 1 (mod 4), or -Δ is not prime
+    InvalidDiscriminant(&'static str),
+}
+
+impl fmt::Display for VdfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VdfError::MalformedProof(reason) => write!(f, "malformed proof: {}", reason),
+            VdfError::UnsupportedVersion(version) => write!(f, "unsupported proof version {}", version),
+            VdfError::VersionMismatch { expected, found } => write!(
+                f,
+                "proof has version {} but the parameters expect version {}",
+                *found as u8, *expected as u8
+            ),
+            VdfError::MalformedEncoding(reason) => write!(f, "malformed form encoding: {}", reason),
+            VdfError::DiscriminantMismatch => write!(f, "discriminants do not match"),
+            VdfError::InvalidForm(err) => write!(f, "invalid form: {}", err),
+            VdfError::TrailingBytes(count) => write!(f, "{} unexpected trailing bytes", count),
+            VdfError::ProofRejected => write!(f, "proof equation π^ℓ · g^r = y does not hold"),
+            VdfError::InvalidParams(reason) => write!(f, "invalid VDF parameters: {}", reason),
+            VdfError::InvalidDiscriminant(reason) => write!(f, "invalid discriminant: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for VdfError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            VdfError::InvalidForm(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<FormError> for VdfError {
+    fn from(err: FormError) -> Self {
+        VdfError::InvalidForm(err)
+    }
+}
@@ -3,9 +3,21 @@
 //! This library provides a complete implementation of the Wesolowski Verifiable Delay Function (VDF)
 //! using binary quadratic forms and class groups. The implementation includes:
 //!
-//! - Class group operations for binary quadratic forms
-//! - Cryptographic utilities for discriminant generation and primality testing
+//! - Class group operations for binary quadratic forms, including prime forms
+//! - Prime discriminant generation compatible with Chia's `create_discriminant`, and primality testing
+//! - Hashing challenges into a fixed public class group
+//! - Validated discriminants, so the class group can be set up once and shared
 //! - Complete VDF computation, proof generation, and verification
+//! - Canonical fixed-size and Chia-compatible compressed encodings of forms
+//! - A typed proof with a strict, versioned binary encoding
+//! - Transcript-based Fiat-Shamir challenges bound to Δ, g, y, t and a context
+//! - Configurable parameters for discriminant size, challenge size, hash and proof format
+//! - On-the-fly proof generation from checkpoints stored during evaluation
+//! - Checkpoint files for resuming interrupted evaluations
+//! - Progress reporting and cancellation for long evaluations
+//! - Typed errors that explain why an input or proof was rejected
+//! - Optional `serde` support (feature `serde`) for forms, proofs, parameters and VDF instances
+//! - Optional `tracing` spans and events (feature `tracing`); the library never prints to stdout
 //!
 //! # Example
 //!
@@ -18,9 +30,25 @@
 //! assert!(is_valid);
 //! ```
 
+pub mod checkpoint;
 pub mod class_group;
 pub mod crypto;
+pub mod discriminant;
+pub mod error;
+pub mod params;
+pub mod progress;
+pub mod proof;
+pub mod prover;
+mod trace;
+pub mod transcript;
 pub mod vdf;
 
-pub use class_group::ClassGroupElement;
+#[cfg(feature = "serde")]
+mod serde_impls;
+
+pub use class_group::{ClassGroupElement, FormError};
+pub use discriminant::Discriminant;
+pub use error::VdfError;
+pub use params::{HashFunction, VdfParams};
+pub use proof::WesolowskiProof;
 pub use vdf::WesolowskiVDF;
\ No newline at end of file
@@ -4,14 +4,11 @@
 //! implementation using class groups of binary quadratic forms.
 
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use std::process;
 use std::time::Duration;
 
-mod class_group;
-mod crypto;
-mod vdf;
-
-use class_group::ClassGroupElement;
-use vdf::WesolowskiVDF;
+use wesolowski_vdf::{ClassGroupElement, VdfError, WesolowskiProof, WesolowskiVDF};
 
 #[derive(Parser)]
 #[command(name = "wesolowski_vdf")]
@@ -27,13 +24,38 @@ enum Commands {
     Compute {
         /// Challenge input (hex string)
         challenge: String,
+        /// Context string bound into the proof challenge
+        #[arg(long, default_value = "")]
+        context: String,
         /// Number of iterations (difficulty)
         iterations: u64,
+        /// Checkpoint file for a resumable computation (optional)
+        #[arg(long)]
+        checkpoint: Option<PathBuf>,
+        /// Number of squarings between two checkpoint saves
+        #[arg(long, default_value_t = 100_000)]
+        save_every: u64,
+    },
+    /// Resume an interrupted VDF computation from its checkpoint file
+    Resume {
+        /// Challenge input (hex string)
+        challenge: String,
+        /// Context string bound into the proof challenge
+        #[arg(long, default_value = "")]
+        context: String,
+        /// Checkpoint file written by `compute --checkpoint`
+        checkpoint: PathBuf,
+        /// Number of squarings between two checkpoint saves
+        #[arg(long, default_value_t = 100_000)]
+        save_every: u64,
     },
     /// Verify VDF proof
     Verify {
         /// Challenge input (hex string)
         challenge: String,
+        /// Context string bound into the proof challenge
+        #[arg(long, default_value = "")]
+        context: String,
         /// Number of iterations (difficulty)
         iterations: u64,
         /// Proof to verify (hex string)
@@ -109,6 +131,26 @@ fn benchmark_iterations() -> u64 {
     10
 }
 
+/// Print a computed output and its proof
+fn print_output(output: &ClassGroupElement, proof: &WesolowskiProof) {
+    println!("Output a: {}", output.a);
+    println!("Output b: {}", output.b);
+    println!("Output c: {}", output.c);
+    println!("Proof: {}", hex::encode(proof.to_bytes()));
+}
+
+/// Parse a decimal form coefficient given on the command line
+fn parse_coefficient(name: &str, value: &str) -> BigInt {
+    BigInt::parse_bytes(value.as_bytes(), 10)
+        .unwrap_or_else(|| exit_with_error(format!("invalid {}: {:?} is not a decimal integer", name, value)))
+}
+
+/// Report an error on stderr and exit with a failure status
+fn exit_with_error(err: impl std::fmt::Display) -> ! {
+    eprintln!("Error: {}", err);
+    process::exit(1);
+}
+
 /// Main function - entry point for the CLI application
 /// 
 /// Parses command line arguments and dispatches to appropriate VDF operations.
@@ -116,43 +158,56 @@ fn main() {
     let cli = Cli::parse();
     
     match cli.command {
-        Commands::Compute { challenge, iterations } => {
+        Commands::Compute { challenge, context, iterations, checkpoint, save_every } => {
             let challenge_bytes = hex::decode(&challenge).unwrap_or_else(|_| challenge.into_bytes());
-            let vdf = WesolowskiVDF::new(&challenge_bytes);
+            let vdf = WesolowskiVDF::new(&challenge_bytes).with_context(context.as_bytes());
             
             println!("Computing Real Wesolowski VDF with {} iterations...", iterations);
-            let (output, proof) = vdf.compute(iterations);
+            let start = Instant::now();
+            let (output, proof) = match checkpoint {
+                Some(path) => vdf.compute_checkpointed(iterations, &path, save_every).unwrap_or_else(|err| exit_with_error(err)),
+                None => vdf.compute(iterations),
+            };
+            println!("Computation took: {:?}", start.elapsed());
             
-            println!("Output a: {}", output.a);
-            println!("Output b: {}", output.b);
-            println!("Output c: {}", output.c);
-            println!("Proof: {}", hex::encode(&proof));
+            print_output(&output, &proof);
         }
         
-        Commands::Verify { challenge, iterations, proof, output_a, output_b, output_c } => {
+        Commands::Resume { challenge, context, checkpoint, save_every } => {
             let challenge_bytes = hex::decode(&challenge).unwrap_or_else(|_| challenge.into_bytes());
-            let proof_bytes = hex::decode(&proof).expect("Invalid proof hex");
-            let vdf = WesolowskiVDF::new(&challenge_bytes);
+            let vdf = WesolowskiVDF::new(&challenge_bytes).with_context(context.as_bytes());
+            
+            let start = Instant::now();
+            let (output, proof) = vdf.resume(&checkpoint, save_every).unwrap_or_else(|err| exit_with_error(err));
+            println!("Computation took: {:?}", start.elapsed());
+            
+            print_output(&output, &proof);
+        }
+        
+        Commands::Verify { challenge, context, iterations, proof, output_a, output_b, output_c } => {
+            let challenge_bytes = hex::decode(&challenge).unwrap_or_else(|_| challenge.into_bytes());
+            let proof_bytes = hex::decode(&proof).unwrap_or_else(|err| exit_with_error(format!("invalid proof hex: {}", err)));
+            let vdf = WesolowskiVDF::new(&challenge_bytes).with_context(context.as_bytes());
             
             let output = if let (Some(a_str), Some(b_str), Some(c_str)) = (output_a, output_b, output_c) {
                 // Use provided output values
-                let a = BigInt::parse_bytes(a_str.as_bytes(), 10).expect("Invalid output_a");
-                let b = BigInt::parse_bytes(b_str.as_bytes(), 10).expect("Invalid output_b");
-                let _ = BigInt::parse_bytes(c_str.as_bytes(), 10).expect("Invalid output_c");
-                ClassGroupElement::new(a, b, vdf.discriminant.clone())
+                let a = parse_coefficient("output_a", &a_str);
+                let b = parse_coefficient("output_b", &b_str);
+                let c = parse_coefficient("output_c", &c_str);
+                ClassGroupElement::try_new(a, b, c, vdf.discriminant.clone())
+                    .unwrap_or_else(|err| exit_with_error(VdfError::InvalidForm(err)))
             } else {
                 // Fall back to recomputing (this is what was causing the issue)
                 let (computed_output, _) = vdf.compute(iterations);
-                println!("Debug: Using computed output for verification (fallback mode)");
+                eprintln!("Note: no output given, verifying against a recomputed output");
                 computed_output
             };
             
-            let is_valid = vdf.verify(&output, &proof_bytes, iterations);
-            
-            if is_valid {
-                println!("Proof is valid");
-            } else {
-                println!("Proof is invalid");
+            let result = WesolowskiProof::from_bytes(&proof_bytes, &vdf.discriminant)
+                .and_then(|proof| vdf.try_verify(&output, &proof, iterations));
+            match result {
+                Ok(()) => println!("Proof is valid"),
+                Err(err) => println!("Proof is invalid: {}", err),
             }
         }
         
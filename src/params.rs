@@ -0,0 +1,264 @@
+//! VDF Parameters
+//!
+//! Different deployments need different security levels, so the sizes and
+//! primitives of the construction are collected in `VdfParams` instead of being
+//! fixed in code. The prover and the verifier must use identical parameters;
+//! `VdfParams::to_bytes` gives a canonical encoding they can exchange or compare,
+//! and `fingerprint` a short digest of it.
+//!
+//! # Parameters
+//!
+//! - **Discriminant bits**: size of the class group discriminant Δ
+//! - **Challenge bits**: size of the Fiat-Shamir challenge prime ℓ
+//! - **Hash function**: hash used for the challenge transcript and ℓ
+//! - **Domain separator**: label that starts every challenge transcript
+//! - **Proof version**: wire format and challenge derivation of proofs
+//!
+//! Version 2 proofs predate these parameters; their challenge is always a
+//! 256-bit prime derived with SHA-256, whatever the hash and challenge size.
+//!
+//! # Encoding (version 1)
+//!
+//! | Field             | Encoding                          |
+//! |-------------------|-----------------------------------|
+//! | magic             | the 8 bytes `WVDFPARM`            |
+//! | version           | u8, currently 1                   |
+//! | discriminant bits | u32                               |
+//! | challenge bits    | u32                               |
+//! | hash function     | u8, 1 = SHA-256, 2 = SHA-512      |
+//! | proof version     | u8                                |
+//! | domain separator  | u32 length + bytes                |
+//!
+//! All integers are big-endian.
+
+use sha2::{Digest, Sha256, Sha512};
+use std::fmt;
+
+use crate::error::VdfError;
+use crate::proof::ProofVersion;
+use crate::transcript::CHALLENGE_DOMAIN;
+use crate::vdf::WesolowskiVDF;
+
+/// Magic bytes at the start of encoded parameters
+pub const PARAMS_MAGIC: &[u8; 8] = b"WVDFPARM";
+
+/// Current version of the parameter encoding
+pub const PARAMS_VERSION: u8 = 1;
+
+/// Accepted range of discriminant sizes in bits
+pub const DISCRIMINANT_BITS: std::ops::RangeInclusive<usize> = 512..=16384;
+
+/// Accepted range of challenge prime sizes in bits
+pub const CHALLENGE_BITS: std::ops::RangeInclusive<usize> = 64..=1024;
+
+/// Longest accepted domain separator in bytes
+pub const MAX_DOMAIN_LEN: usize = 1024;
+
+/// Hash function used for Fiat-Shamir challenges
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HashFunction {
+    /// SHA-256
+    Sha256,
+    /// SHA-512
+    Sha512,
+}
+
+impl HashFunction {
+    /// Hash `data`
+    pub fn digest(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            HashFunction::Sha256 => Sha256::digest(data).to_vec(),
+            HashFunction::Sha512 => Sha512::digest(data).to_vec(),
+        }
+    }
+
+    /// Identifier used in the parameter encoding
+    pub fn id(self) -> u8 {
+        match self {
+            HashFunction::Sha256 => 1,
+            HashFunction::Sha512 => 2,
+        }
+    }
+
+    /// Look up a hash function by its identifier
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            1 => Some(HashFunction::Sha256),
+            2 => Some(HashFunction::Sha512),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for HashFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HashFunction::Sha256 => write!(f, "SHA-256"),
+            HashFunction::Sha512 => write!(f, "SHA-512"),
+        }
+    }
+}
+
+/// Parameters shared by the prover and the verifier
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VdfParams {
+    /// Bit length of the discriminant
+    pub discriminant_bits: usize,
+    /// Bit length of the challenge prime
+    pub challenge_bits: usize,
+    /// Hash function of the challenge transcript and prime
+    pub hash: HashFunction,
+    /// Domain separator that starts every challenge transcript
+    pub domain: Vec<u8>,
+    /// Format of generated proofs; the verifier accepts only this version
+    pub proof_version: ProofVersion,
+}
+
+impl Default for VdfParams {
+    /// 1024-bit discriminant, 256-bit challenge, SHA-256 and version 3 proofs
+    fn default() -> Self {
+        Self {
+            discriminant_bits: 1024,
+            challenge_bits: 256,
+            hash: HashFunction::Sha256,
+            domain: CHALLENGE_DOMAIN.to_vec(),
+            proof_version: ProofVersion::Transcript,
+        }
+    }
+}
+
+impl VdfParams {
+    /// Start building parameters from the defaults
+    pub fn builder() -> VdfParamsBuilder {
+        VdfParamsBuilder { params: Self::default() }
+    }
+
+    /// Check that every parameter is within its accepted range
+    pub fn validate(&self) -> Result<(), VdfError> {
+        if !DISCRIMINANT_BITS.contains(&self.discriminant_bits) {
+            return Err(VdfError::InvalidParams("discriminant size out of range"));
+        }
+        if !CHALLENGE_BITS.contains(&self.challenge_bits) {
+            return Err(VdfError::InvalidParams("challenge size out of range"));
+        }
+        if self.domain.is_empty() || self.domain.len() > MAX_DOMAIN_LEN {
+            return Err(VdfError::InvalidParams("domain separator must have 1 to 1024 bytes"));
+        }
+        Ok(())
+    }
+
+    /// Canonical encoding of the parameters
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(PARAMS_MAGIC.len() + 15 + self.domain.len());
+        out.extend_from_slice(PARAMS_MAGIC);
+        out.push(PARAMS_VERSION);
+        out.extend_from_slice(&(self.discriminant_bits as u32).to_be_bytes());
+        out.extend_from_slice(&(self.challenge_bits as u32).to_be_bytes());
+        out.push(self.hash.id());
+        out.push(self.proof_version as u8);
+        out.extend_from_slice(&(self.domain.len() as u32).to_be_bytes());
+        out.extend_from_slice(&self.domain);
+        out
+    }
+
+    /// Decode and validate parameters
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VdfError> {
+        let header = PARAMS_MAGIC.len() + 15;
+        if bytes.len() < header {
+            return Err(VdfError::InvalidParams("encoding is too short"));
+        }
+        let (magic, rest) = bytes.split_at(PARAMS_MAGIC.len());
+        if magic != PARAMS_MAGIC {
+            return Err(VdfError::InvalidParams("bad magic"));
+        }
+        if rest[0] != PARAMS_VERSION {
+            return Err(VdfError::UnsupportedVersion(rest[0]));
+        }
+        
+        let u32_at = |offset: usize| {
+            u32::from_be_bytes([rest[offset], rest[offset + 1], rest[offset + 2], rest[offset + 3]]) as usize
+        };
+        let discriminant_bits = u32_at(1);
+        let challenge_bits = u32_at(5);
+        let hash = HashFunction::from_id(rest[9]).ok_or(VdfError::InvalidParams("unknown hash function"))?;
+        let proof_version = ProofVersion::from_u8(rest[10]).ok_or(VdfError::UnsupportedVersion(rest[10]))?;
+        let domain_len = u32_at(11);
+        let domain = &rest[15..];
+        if domain.len() < domain_len {
+            return Err(VdfError::InvalidParams("encoding is too short"));
+        }
+        if domain.len() > domain_len {
+            return Err(VdfError::TrailingBytes(domain.len() - domain_len));
+        }
+        
+        let params = Self {
+            discriminant_bits,
+            challenge_bits,
+            hash,
+            domain: domain.to_vec(),
+            proof_version,
+        };
+        params.validate()?;
+        Ok(params)
+    }
+
+    /// SHA-256 of the canonical encoding, for comparing parameters out of band
+    pub fn fingerprint(&self) -> [u8; 32] {
+        Sha256::digest(self.to_bytes()).into()
+    }
+}
+
+/// Builder for `VdfParams`
+/// 
+/// Unset fields keep their defaults. `build` validates the result.
+#[derive(Clone, Debug)]
+pub struct VdfParamsBuilder {
+    params: VdfParams,
+}
+
+impl VdfParamsBuilder {
+    /// Set the discriminant size in bits (512 to 16384)
+    pub fn discriminant_bits(mut self, bits: usize) -> Self {
+        self.params.discriminant_bits = bits;
+        self
+    }
+
+    /// Set the challenge prime size in bits (64 to 1024)
+    pub fn challenge_bits(mut self, bits: usize) -> Self {
+        self.params.challenge_bits = bits;
+        self
+    }
+
+    /// Set the hash function of the challenge derivation
+    pub fn hash(mut self, hash: HashFunction) -> Self {
+        self.params.hash = hash;
+        self
+    }
+
+    /// Set the domain separator of the challenge transcript
+    pub fn domain(mut self, domain: &[u8]) -> Self {
+        self.params.domain = domain.to_vec();
+        self
+    }
+
+    /// Set the proof format
+    pub fn proof_version(mut self, version: ProofVersion) -> Self {
+        self.params.proof_version = version;
+        self
+    }
+
+    /// Validate and return the parameters
+    pub fn build(self) -> Result<VdfParams, VdfError> {
+        self.params.validate()?;
+        Ok(self.params)
+    }
+
+    /// Validate the parameters and create a VDF instance for `challenge`
+    /// 
+    /// The discriminant of `discriminant_bits` bits is generated from the
+    /// challenge, as in `WesolowskiVDF::new`.
+    pub fn build_vdf(self, challenge: &[u8]) -> Result<WesolowskiVDF, VdfError> {
+        self.params.validate()?;
+        Ok(WesolowskiVDF::build(self.params, challenge))
+    }
+}
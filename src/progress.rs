@@ -0,0 +1,206 @@
+//! Progress Reporting and Cancellation for Long Evaluations
+//!
+//! A VDF evaluation can run for hours, so callers need to observe it and to stop it
+//! when its result is no longer wanted. The evaluation loop runs in chunks of a fixed
+//! number of squarings; between two chunks it reports a `Progress` snapshot to a
+//! `ProgressObserver` and checks a `CancellationToken`.
+//!
+//! # Cancellation
+//!
+//! A cancelled evaluation returns `Cancelled`, which owns the partial `Evaluator`.
+//! Nothing is lost: the evaluator can be handed back to
+//! `WesolowskiVDF::evaluate_with_progress` or saved with the `checkpoint` module.
+//! The evaluator records the generator it squares, so handing it to a VDF instance
+//! of another generator fails with `EvaluationError::GeneratorMismatch`.
+
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::prover::Evaluator;
+
+/// Snapshot of a running evaluation
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Progress {
+    /// Squarings performed so far
+    pub iterations_done: u64,
+    /// Total number of squarings of the evaluation
+    pub iterations_total: u64,
+    /// Time spent in this run, excluding work restored from earlier runs
+    pub elapsed: Duration,
+    /// Squarings per second measured over this run
+    pub rate: f64,
+    /// Estimated time to completion, once a rate is known
+    pub eta: Option<Duration>,
+}
+
+impl Progress {
+    /// Fraction of the evaluation that is done, in [0, 1]
+    pub fn fraction(&self) -> f64 {
+        if self.iterations_total == 0 {
+            1.0
+        } else {
+            self.iterations_done as f64 / self.iterations_total as f64
+        }
+    }
+}
+
+/// Receiver of progress snapshots
+/// 
+/// Closures taking `&Progress` implement this trait.
+pub trait ProgressObserver {
+    /// Called after every chunk of squarings
+    fn on_progress(&mut self, progress: &Progress);
+}
+
+impl<F: FnMut(&Progress)> ProgressObserver for F {
+    fn on_progress(&mut self, progress: &Progress) {
+        self(progress)
+    }
+}
+
+/// Observer that ignores all progress reports
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoProgress;
+
+impl ProgressObserver for NoProgress {
+    fn on_progress(&mut self, _progress: &Progress) {}
+}
+
+/// Shared flag used to request that an evaluation stops
+/// 
+/// Clones share the same flag, so one clone can be handed to the evaluation and
+/// another kept by the scheduler that may cancel it.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Create a token that is not cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation of every evaluation holding a clone of this token
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether cancellation has been requested
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Error returned by an evaluation that was cancelled
+/// 
+/// It carries the partial state so that the work done so far can be resumed.
+#[derive(Debug)]
+pub struct Cancelled {
+    /// The evaluation state at the moment of cancellation
+    pub evaluator: Box<Evaluator>,
+}
+
+impl Cancelled {
+    /// Number of squarings completed before the cancellation
+    pub fn iterations_done(&self) -> u64 {
+        self.evaluator.iterations_done()
+    }
+
+    /// Take back the partial evaluation state
+    pub fn into_evaluator(self) -> Evaluator {
+        *self.evaluator
+    }
+}
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "evaluation cancelled after {} of {} iterations",
+            self.evaluator.iterations_done(),
+            self.evaluator.iterations()
+        )
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Error returned when continuing an evaluation
+#[derive(Debug)]
+pub enum EvaluationError {
+    /// The evaluation was cancelled again
+    Cancelled(Cancelled),
+    /// The evaluator squares a different generator than the VDF instance
+    /// 
+    /// The evaluator is returned unchanged.
+    GeneratorMismatch(Box<Evaluator>),
+}
+
+impl fmt::Display for EvaluationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvaluationError::Cancelled(cancelled) => write!(f, "{}", cancelled),
+            EvaluationError::GeneratorMismatch(_) => {
+                write!(f, "evaluation belongs to a different discriminant or generator")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EvaluationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EvaluationError::Cancelled(cancelled) => Some(cancelled),
+            EvaluationError::GeneratorMismatch(_) => None,
+        }
+    }
+}
+
+impl From<Cancelled> for EvaluationError {
+    fn from(cancelled: Cancelled) -> Self {
+        EvaluationError::Cancelled(cancelled)
+    }
+}
+
+/// Advance an evaluator to the end in chunks of `check_every` squarings
+/// 
+/// The token is checked before each chunk and the observer is notified after it.
+pub fn drive(
+    mut evaluator: Evaluator,
+    observer: &mut dyn ProgressObserver,
+    token: &CancellationToken,
+    check_every: u64,
+) -> Result<Evaluator, Cancelled> {
+    let start = Instant::now();
+    let first = evaluator.iterations_done();
+    let chunk = check_every.max(1);
+    
+    while !evaluator.is_finished() {
+        if token.is_cancelled() {
+            return Err(Cancelled { evaluator: Box::new(evaluator) });
+        }
+        evaluator.run(chunk);
+        
+        let elapsed = start.elapsed();
+        let done = evaluator.iterations_done();
+        let rate = (done - first) as f64 / elapsed.as_secs_f64().max(f64::MIN_POSITIVE);
+        let remaining = evaluator.iterations() - done;
+        let eta = if rate > 0.0 {
+            Duration::try_from_secs_f64(remaining as f64 / rate).ok()
+        } else {
+            None
+        };
+        observer.on_progress(&Progress {
+            iterations_done: done,
+            iterations_total: evaluator.iterations(),
+            elapsed,
+            rate,
+            eta,
+        });
+    }
+    
+    Ok(evaluator)
+}
@@ -0,0 +1,283 @@
+//! Wesolowski Proof Type and Wire Format
+//!
+//! A proof consists of the single element π = g^⌊2^t/ℓ⌋. The verifier derives
+//! the remainder r = 2^t mod ℓ of the equation y = π^ℓ · g^r itself, so a proof
+//! has the size of one form regardless of t. This module gives it a typed
+//! representation and a strict, versioned binary encoding.
+//!
+//! # Versions
+//!
+//! The version byte also selects how the verifier derives the challenge prime ℓ:
+//!
+//! - **Version 2** hashes the serialized generator and output only
+//! - **Version 3** derives ℓ from a `Transcript` over Δ, g, y, t and a context
+//!   string, see `transcript::wesolowski_challenge`
+//!
+//! New proofs use version 3. Version 2 proofs are still decoded and verified,
+//! but only by an instance whose `VdfParams` select `ProofVersion::Legacy`. The
+//! default verifier rejects them with `VdfError::VersionMismatch`, so a prover
+//! cannot downgrade a proof to the weaker challenge derivation.
+//!
+//! # Wire Format (versions 2 and 3)
+//!
+//! All integers are big-endian. Magnitudes are minimal: no leading zero bytes,
+//! and zero is the single byte `00` with a positive sign.
+//!
+//! | Field             | Encoding                                               |
+//! |-------------------|--------------------------------------------------------|
+//! | version           | u8, 2 or 3                                             |
+//! | discriminant size | u16, bit length of \|Δ\|                               |
+//! | π                 | a, b, each as u32 length + sign byte + magnitude       |
+//!
+//! The sign byte is 0 for non-negative and 1 for negative values. The coefficient
+//! c of π is not transmitted; it is determined by a, b and Δ, so 4a must divide
+//! b² - Δ, and π must be reduced. Decoding
+//! rejects unknown versions, a size class that does not match the expected
+//! discriminant, non-canonical lengths and trailing bytes, so every proof has
+//! exactly one encoding.
+//!
+//! Version 1 proofs also carried q = ⌊2^t/ℓ⌋ and r, which made them t bits long.
+//! They are no longer accepted.
+
+use num_bigint::{BigInt, Sign};
+use num_integer::Integer;
+use num_traits::{Signed, Zero};
+
+use crate::class_group::{ClassGroupElement, FormError};
+use crate::error::VdfError;
+
+/// Version of the proof wire format written for new proofs
+pub const PROOF_VERSION: u8 = ProofVersion::Transcript as u8;
+
+/// Proof format versions, which differ in how the challenge prime is derived
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofVersion {
+    /// Version 2: ℓ is hashed from the generator and output only
+    Legacy = 2,
+    /// Version 3: ℓ is derived from a transcript over Δ, g, y, t and a context
+    Transcript = 3,
+}
+
+impl ProofVersion {
+    /// Look up a version byte
+    pub fn from_u8(version: u8) -> Option<Self> {
+        match version {
+            2 => Some(ProofVersion::Legacy),
+            3 => Some(ProofVersion::Transcript),
+            _ => None,
+        }
+    }
+}
+
+/// A Wesolowski proof for the claim y = g^(2^t)
+#[derive(Clone, Debug, PartialEq)]
+pub struct WesolowskiProof {
+    /// The proof element π = g^⌊2^t/ℓ⌋
+    pub pi: ClassGroupElement,
+    /// The format version, which fixes how ℓ is derived
+    pub version: ProofVersion,
+}
+
+impl WesolowskiProof {
+    /// Encode the proof in the wire format of its version
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = vec![self.version as u8];
+        out.extend_from_slice(&size_class(&self.pi.discriminant).to_be_bytes());
+        
+        for coefficient in [&self.pi.a, &self.pi.b] {
+            let (sign, magnitude) = coefficient.to_bytes_be();
+            out.extend_from_slice(&(magnitude.len() as u32).to_be_bytes());
+            out.push(if sign == Sign::Minus { 1 } else { 0 });
+            out.extend_from_slice(&magnitude);
+        }
+        
+        out
+    }
+
+    /// Decode a proof for the class group of `discriminant`
+    /// 
+    /// The input must be exactly one canonical encoding; see the module
+    /// documentation for the layout.
+    pub fn from_bytes(bytes: &[u8], discriminant: &BigInt) -> Result<Self, VdfError> {
+        let mut reader = Reader { bytes, offset: 0 };
+        let version = reader.take(1)?[0];
+        let version = ProofVersion::from_u8(version).ok_or(VdfError::UnsupportedVersion(version))?;
+        let size = reader.take(2)?;
+        if u16::from_be_bytes([size[0], size[1]]) != size_class(discriminant) {
+            return Err(VdfError::DiscriminantMismatch);
+        }
+        
+        // No coefficient of a form of this discriminant needs more bytes than |Δ|
+        let max_len = discriminant.bits().div_ceil(8) as usize;
+        let a = reader.signed(max_len)?;
+        let b = reader.signed(max_len)?;
+        if reader.offset != bytes.len() {
+            return Err(VdfError::TrailingBytes(bytes.len() - reader.offset));
+        }
+        
+        if !a.is_positive() {
+            return Err(VdfError::InvalidForm(FormError::NonPositiveLeadingCoefficient));
+        }
+        let (c, remainder) = (&b * &b - discriminant).div_rem(&(4 * &a));
+        if !remainder.is_zero() {
+            return Err(VdfError::InvalidForm(FormError::DiscriminantMismatch));
+        }
+        let pi = ClassGroupElement::try_new(a, b, c, discriminant.clone())?;
+        if !pi.is_reduced() {
+            return Err(VdfError::InvalidForm(FormError::NotReduced));
+        }
+        Ok(Self { pi, version })
+    }
+}
+
+/// Discriminant size class written into the proof header
+fn size_class(discriminant: &BigInt) -> u16 {
+    discriminant.bits().min(u16::MAX as u64) as u16
+}
+
+/// Cursor over an encoded proof
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], VdfError> {
+        let end = self.offset.checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or(VdfError::MalformedProof("unexpected end of data"))?;
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn length(&mut self, max_len: usize) -> Result<usize, VdfError> {
+        let bytes = self.take(4)?;
+        let len = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+        if len > max_len {
+            return Err(VdfError::MalformedProof("integer is too large"));
+        }
+        Ok(len)
+    }
+
+    fn magnitude(&mut self, len: usize) -> Result<&'a [u8], VdfError> {
+        match self.take(len)? {
+            [] => Err(VdfError::MalformedProof("empty integer")),
+            [0, _, ..] => Err(VdfError::MalformedProof("non-canonical integer length")),
+            magnitude => Ok(magnitude),
+        }
+    }
+
+    fn signed(&mut self, max_len: usize) -> Result<BigInt, VdfError> {
+        let len = self.length(max_len)?;
+        let sign = match self.take(1)?[0] {
+            0 => Sign::Plus,
+            1 => Sign::Minus,
+            _ => return Err(VdfError::MalformedProof("invalid sign byte")),
+        };
+        let magnitude = self.magnitude(len)?;
+        if sign == Sign::Minus && magnitude == [0] {
+            return Err(VdfError::MalformedProof("negative zero"));
+        }
+        Ok(BigInt::from_bytes_be(sign, magnitude))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::create_discriminant;
+
+    fn proof(version: ProofVersion) -> WesolowskiProof {
+        let discriminant = create_discriminant(b"proof-test", 512);
+        let pi = ClassGroupElement::from_hash(discriminant, b"pi").unwrap();
+        WesolowskiProof { pi, version }
+    }
+
+    #[test]
+    fn encoding_round_trips() {
+        for version in [ProofVersion::Legacy, ProofVersion::Transcript] {
+            let proof = proof(version);
+            let bytes = proof.to_bytes();
+            assert_eq!(bytes[0], version as u8);
+            assert_eq!(WesolowskiProof::from_bytes(&bytes, &proof.pi.discriminant), Ok(proof));
+        }
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        let proof = proof(ProofVersion::Transcript);
+        let mut bytes = proof.to_bytes();
+        for version in [0, 1, 4, 255] {
+            bytes[0] = version;
+            assert_eq!(
+                WesolowskiProof::from_bytes(&bytes, &proof.pi.discriminant),
+                Err(VdfError::UnsupportedVersion(version))
+            );
+        }
+    }
+
+    #[test]
+    fn trailing_and_truncated_input_is_rejected() {
+        let proof = proof(ProofVersion::Transcript);
+        let discriminant = &proof.pi.discriminant;
+        let bytes = proof.to_bytes();
+        
+        for extra in [1, 3] {
+            let mut longer = bytes.clone();
+            longer.extend(std::iter::repeat_n(0, extra));
+            assert_eq!(WesolowskiProof::from_bytes(&longer, discriminant), Err(VdfError::TrailingBytes(extra)));
+        }
+        for len in 0..bytes.len() {
+            assert_eq!(
+                WesolowskiProof::from_bytes(&bytes[..len], discriminant),
+                Err(VdfError::MalformedProof("unexpected end of data")),
+                "{} bytes",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn non_canonical_input_is_rejected() {
+        let proof = proof(ProofVersion::Transcript);
+        let discriminant = &proof.pi.discriminant;
+        let bytes = proof.to_bytes();
+        let a_len = u32::from_be_bytes(bytes[3..7].try_into().unwrap()) as usize;
+        
+        let mut wrong_size = bytes.clone();
+        wrong_size[2] ^= 1;
+        let mut bad_sign = bytes.clone();
+        bad_sign[7] = 2;
+        let mut negative_a = bytes.clone();
+        negative_a[7] = 1;
+        // a with a leading zero byte
+        let mut padded = bytes[..3].to_vec();
+        padded.extend_from_slice(&(a_len as u32 + 1).to_be_bytes());
+        padded.extend_from_slice(&[0, 0]);
+        padded.extend_from_slice(&bytes[8..]);
+        
+        for (input, expected) in [
+            (wrong_size, VdfError::DiscriminantMismatch),
+            (bad_sign, VdfError::MalformedProof("invalid sign byte")),
+            (negative_a, VdfError::InvalidForm(FormError::NonPositiveLeadingCoefficient)),
+            (padded, VdfError::MalformedProof("non-canonical integer length")),
+        ] {
+            assert_eq!(WesolowskiProof::from_bytes(&input, discriminant), Err(expected));
+        }
+    }
+
+    #[test]
+    fn unreduced_proof_element_is_rejected() {
+        let proof = proof(ProofVersion::Transcript);
+        let pi = &proof.pi;
+        let unreduced = WesolowskiProof {
+            pi: ClassGroupElement::new(pi.a.clone(), &pi.b + 2 * &pi.a, pi.discriminant.clone()),
+            version: proof.version,
+        };
+        assert_eq!(
+            WesolowskiProof::from_bytes(&unreduced.to_bytes(), &pi.discriminant),
+            Err(VdfError::InvalidForm(FormError::NotReduced))
+        );
+    }
+}
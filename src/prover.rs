@@ -0,0 +1,271 @@
+//! On-the-fly Wesolowski Proof Generation
+//!
+//! This module implements the proof algorithm from Section 4.1 of Wesolowski's paper.
+//! Instead of computing π = g^⌊2^t/ℓ⌋ with a t-bit exponent after the evaluation,
+//! the sequential squaring loop stores a checkpoint every k·l squarings, and the proof
+//! is assembled from those checkpoints once the challenge prime ℓ is known.
+//!
+//! # Algorithm Overview
+//!
+//! Write ⌊2^t/ℓ⌋ in base 2^k. Its digit of weight 2^(k·p) is
+//! b_p = ⌊2^k · (2^(t - k(p+1)) mod ℓ) / ℓ⌋, so each digit costs one small modular
+//! exponentiation and the big quotient is never materialised. With checkpoints
+//! C_i = g^(2^(i·k·l)) the proof is
+//!
+//! π = Π_j ( Π_i C_i^(b_(i·l + j)) )^(2^(k·j))
+//!
+//! which is evaluated with a Horner scheme over j, grouping the checkpoints of each
+//! row by digit value so that every checkpoint is composed exactly once.
+//!
+//! # Performance
+//!
+//! - Proving time: about t/k compositions plus l·k squarings
+//! - Memory: t/(k·l) stored forms
+
+use num_bigint::BigInt;
+use num_traits::{ToPrimitive, Zero};
+
+use crate::class_group::ClassGroupElement;
+use crate::crypto::mod_pow;
+
+/// Upper limit on the number of checkpoints kept in memory
+/// 
+/// Above this, the block count l grows instead so that memory stays bounded.
+pub const MAX_CHECKPOINTS: u64 = 1 << 20;
+
+/// Largest digit size k, in bits, chosen by `ProofParameters::for_iterations`
+/// 
+/// The prover keeps 2^k partial products per row, so k is also the largest value
+/// accepted from a checkpoint file.
+pub const MAX_DIGIT_BITS: u32 = 20;
+
+/// Parameters of the k-ary, l-block proof algorithm
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofParameters {
+    /// Number of bits per exponent digit
+    pub k: u32,
+    /// Number of digit rows sharing one checkpoint
+    pub l: u32,
+}
+
+impl ProofParameters {
+    /// Choose (k, l) for a given number of iterations
+    /// 
+    /// The digit size minimises t/k + l·2^k, which is reached near
+    /// k = ln(x) - ln(ln(x)) with x = t·ln(2)/(2l). l stays at 1 unless the
+    /// checkpoints would exceed `MAX_CHECKPOINTS`.
+    pub fn for_iterations(iterations: u64) -> Self {
+        let k = Self::digit_size(iterations, 1);
+        if iterations / k as u64 <= MAX_CHECKPOINTS {
+            return Self { k, l: 1 };
+        }
+        
+        let l = iterations.div_ceil(k as u64 * MAX_CHECKPOINTS) as u32;
+        let k = Self::digit_size(iterations, l);
+        let l = iterations.div_ceil(k as u64 * MAX_CHECKPOINTS).max(1) as u32;
+        Self { k, l }
+    }
+
+    /// Number of squarings between two stored checkpoints
+    pub fn checkpoint_interval(&self) -> u64 {
+        self.k as u64 * self.l as u64
+    }
+
+    fn digit_size(iterations: u64, l: u32) -> u32 {
+        let x = iterations as f64 * std::f64::consts::LN_2 / (2.0 * l as f64);
+        if x <= std::f64::consts::E {
+            return 1;
+        }
+        (x.ln() - x.ln().ln() + 0.25).round().clamp(1.0, MAX_DIGIT_BITS as f64) as u32
+    }
+}
+
+/// Sequential squaring loop that records proof checkpoints
+/// 
+/// The evaluator computes g^(2^t) one squaring at a time and keeps
+/// g^(2^(i·k·l)) for every i with i·k·l < t. It can be advanced in chunks,
+/// which lets callers interleave the evaluation with other work.
+#[derive(Clone, Debug)]
+pub struct Evaluator {
+    generator: ClassGroupElement,
+    iterations: u64,
+    parameters: ProofParameters,
+    index: u64,
+    current: ClassGroupElement,
+    checkpoints: Vec<ClassGroupElement>,
+}
+
+impl Evaluator {
+    /// Start an evaluation of `iterations` squarings of `generator`
+    pub fn new(generator: &ClassGroupElement, iterations: u64) -> Self {
+        Self::with_parameters(generator, iterations, ProofParameters::for_iterations(iterations))
+    }
+
+    /// Start an evaluation with explicit proof parameters
+    pub fn with_parameters(generator: &ClassGroupElement, iterations: u64, parameters: ProofParameters) -> Self {
+        let capacity = iterations.div_ceil(parameters.checkpoint_interval()) as usize;
+        Self {
+            generator: generator.clone(),
+            iterations,
+            parameters,
+            index: 0,
+            current: generator.clone(),
+            checkpoints: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Rebuild an evaluator from saved state
+    pub(crate) fn from_parts(
+        generator: ClassGroupElement,
+        iterations: u64,
+        parameters: ProofParameters,
+        index: u64,
+        current: ClassGroupElement,
+        checkpoints: Vec<ClassGroupElement>,
+    ) -> Self {
+        Self { generator, iterations, parameters, index, current, checkpoints }
+    }
+
+    /// Perform up to `max_steps` squarings and return how many were done
+    pub fn run(&mut self, max_steps: u64) -> u64 {
+        let interval = self.parameters.checkpoint_interval();
+        let steps = max_steps.min(self.iterations - self.index);
+        
+        for _ in 0..steps {
+            if self.index.is_multiple_of(interval) {
+                self.checkpoints.push(self.current.clone());
+            }
+            self.current = self.current.square();
+            self.index += 1;
+        }
+        
+        steps
+    }
+
+    /// Run the evaluation to completion
+    pub fn run_to_end(&mut self) {
+        self.run(self.iterations - self.index);
+    }
+
+    /// Whether all squarings have been performed
+    pub fn is_finished(&self) -> bool {
+        self.index == self.iterations
+    }
+
+    /// The form g being squared
+    pub fn generator(&self) -> &ClassGroupElement {
+        &self.generator
+    }
+
+    /// Total number of squarings of this evaluation
+    pub fn iterations(&self) -> u64 {
+        self.iterations
+    }
+
+    /// Number of squarings performed so far
+    pub fn iterations_done(&self) -> u64 {
+        self.index
+    }
+
+    /// The current form, g^(2^iterations_done)
+    pub fn current(&self) -> &ClassGroupElement {
+        &self.current
+    }
+
+    /// Proof parameters used for the checkpoints
+    pub fn parameters(&self) -> ProofParameters {
+        self.parameters
+    }
+
+    /// Checkpoints recorded so far, g^(2^(i·k·l)) for i = 0, 1, ...
+    pub fn checkpoints(&self) -> &[ClassGroupElement] {
+        &self.checkpoints
+    }
+
+    /// Compute π = g^⌊2^t/ℓ⌋ from the recorded checkpoints
+    /// 
+    /// # Panics
+    /// Panics if the evaluation has not finished.
+    pub fn prove(&self, challenge_prime: &BigInt) -> ClassGroupElement {
+        assert!(self.is_finished(), "proof requested before the evaluation finished");
+        prove(&self.checkpoints, self.parameters, self.iterations, challenge_prime, &self.current.discriminant)
+    }
+}
+
+/// Compute π = g^⌊2^t/ℓ⌋ from checkpoints C_i = g^(2^(i·k·l))
+/// 
+/// `checkpoints` must contain every C_i with i·k·l < t, as recorded by `Evaluator`.
+pub fn prove(
+    checkpoints: &[ClassGroupElement],
+    parameters: ProofParameters,
+    iterations: u64,
+    challenge_prime: &BigInt,
+    discriminant: &BigInt,
+) -> ClassGroupElement {
+    let k = parameters.k as u64;
+    let l = parameters.l as u64;
+    let digits = 1usize << k;
+    let k1 = k / 2;
+    let k0 = k - k1;
+    
+    let two = BigInt::from(2);
+    // Moving one checkpoint down multiplies 2^(t - k(p+1)) by 2^(k·l)
+    let row_step = mod_pow(&two, &BigInt::from(k * l), challenge_prime);
+    
+    let mut x = ClassGroupElement::identity(discriminant.clone());
+    for j in (0..l).rev() {
+        for _ in 0..k {
+            x = x.square();
+        }
+        
+        // ys[b] = product of the checkpoints whose digit in this row equals b
+        let mut ys: Vec<Option<ClassGroupElement>> = vec![None; digits];
+        let rows = checkpoints
+            .len()
+            .min((iterations / k).saturating_sub(j).div_ceil(l) as usize);
+        let mut residue = BigInt::zero();
+        for i in (0..rows).rev() {
+            // Digit position p = i·l + j, valid while k(p+1) ≤ t
+            let p = i as u64 * l + j;
+            residue = if i + 1 == rows {
+                mod_pow(&two, &BigInt::from(iterations - k * (p + 1)), challenge_prime)
+            } else {
+                (&residue * &row_step) % challenge_prime
+            };
+            let digit = ((&residue << k) / challenge_prime)
+                .to_usize()
+                .expect("digit is below 2^k");
+            if digit == 0 {
+                continue;
+            }
+            ys[digit] = Some(match ys[digit].take() {
+                Some(y) => y.compose(&checkpoints[i]),
+                None => checkpoints[i].clone(),
+            });
+        }
+        
+        // Π_b ys[b]^b, splitting b = b1·2^k0 + b0
+        for b1 in 1..1usize << k1 {
+            let z = product((0..1usize << k0).map(|b0| &ys[(b1 << k0) + b0]));
+            if let Some(z) = z {
+                x = x.compose(&z.pow(&BigInt::from(b1 << k0)));
+            }
+        }
+        for b0 in 1..1usize << k0 {
+            let z = product((0..1usize << k1).map(|b1| &ys[(b1 << k0) + b0]));
+            if let Some(z) = z {
+                x = x.compose(&z.pow(&BigInt::from(b0)));
+            }
+        }
+    }
+    
+    x
+}
+
+/// Compose all present elements, or return `None` if there are none
+fn product<'a>(elements: impl Iterator<Item = &'a Option<ClassGroupElement>>) -> Option<ClassGroupElement> {
+    elements.flatten().fold(None, |acc, y| match acc {
+        Some(acc) => Some(y.compose(&acc)),
+        None => Some(y.clone()),
+    })
+}
@@ -0,0 +1,425 @@
+//! Serde Support
+//!
+//! With the `serde` feature, forms, proofs, parameters and VDF instances implement
+//! `Serialize` and `Deserialize`. Each type is written with its canonical encoding,
+//! and decoding applies the same checks as the corresponding `from_bytes` or
+//! `try_deserialize`, so a record read from JSON or CBOR is as trustworthy as one
+//! read from raw bytes.
+//!
+//! | Type                | Binary formats                      | Human-readable formats               |
+//! |---------------------|-------------------------------------|--------------------------------------|
+//! | `ClassGroupElement` | bytes of `serialize`                | a, b, c as decimal strings           |
+//! | `Discriminant`      | minimal two's complement bytes      | decimal string                       |
+//! | `WesolowskiProof`   | Δ and the bytes of `to_bytes`       | Δ in decimal, proof bytes in hex     |
+//! | `VdfParams`         | bytes of `to_bytes`                 | fields, domain separator in hex      |
+//! | `WesolowskiVDF`     | parameters, Δ, generator, context   | the same, Δ in decimal, context in hex |
+//!
+//! Forms are written in the length-prefixed `serialize` layout rather than with
+//! `to_canonical_bytes`: it carries c, so the discriminant follows as b² - 4ac and
+//! a form needs no context to be decoded. Decoding accepts only reduced forms with
+//! minimal lengths, so each form still has a single binary encoding. A proof only
+//! records the size of Δ, so Δ is stored next to it.
+
+use num_bigint::BigInt;
+use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::class_group::ClassGroupElement;
+use crate::discriminant::Discriminant;
+use crate::error::VdfError;
+use crate::params::{HashFunction, VdfParams};
+use crate::proof::{ProofVersion, WesolowskiProof};
+use crate::vdf::WesolowskiVDF;
+
+#[derive(Serialize)]
+#[serde(rename = "ClassGroupElement")]
+struct CoefficientsRef<'a> {
+    #[serde(with = "integer")]
+    a: &'a BigInt,
+    #[serde(with = "integer")]
+    b: &'a BigInt,
+    #[serde(with = "integer")]
+    c: &'a BigInt,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "ClassGroupElement", deny_unknown_fields)]
+struct Coefficients {
+    #[serde(with = "integer")]
+    a: BigInt,
+    #[serde(with = "integer")]
+    b: BigInt,
+    #[serde(with = "integer")]
+    c: BigInt,
+}
+
+impl Serialize for ClassGroupElement {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            CoefficientsRef { a: &self.a, b: &self.b, c: &self.c }.serialize(serializer)
+        } else {
+            serializer.serialize_bytes(&ClassGroupElement::serialize(self))
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ClassGroupElement {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            // Go through the binary layout so that both paths apply the same checks
+            let Coefficients { a, b, c } = Coefficients::deserialize(deserializer)?;
+            let discriminant = &b * &b - 4 * &a * &c;
+            let form = ClassGroupElement { a, b, c, discriminant };
+            ClassGroupElement::try_deserialize_implicit(&ClassGroupElement::serialize(&form))
+                .map_err(de::Error::custom)
+        } else {
+            let bytes = bytes::deserialize(deserializer)?;
+            ClassGroupElement::try_deserialize_implicit(&bytes).map_err(de::Error::custom)
+        }
+    }
+}
+
+impl Serialize for Discriminant {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        integer::serialize(self.as_bigint(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Discriminant {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Discriminant::new(integer::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename = "WesolowskiProof")]
+struct ProofRef<'a> {
+    #[serde(with = "integer")]
+    discriminant: &'a BigInt,
+    #[serde(with = "bytes")]
+    proof: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "WesolowskiProof", deny_unknown_fields)]
+struct ProofRepr {
+    #[serde(with = "integer")]
+    discriminant: BigInt,
+    #[serde(with = "bytes")]
+    proof: Vec<u8>,
+}
+
+impl Serialize for WesolowskiProof {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ProofRef { discriminant: &self.pi.discriminant, proof: self.to_bytes() }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for WesolowskiProof {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let ProofRepr { discriminant, proof } = ProofRepr::deserialize(deserializer)?;
+        WesolowskiProof::from_bytes(&proof, &discriminant).map_err(de::Error::custom)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "VdfParams", deny_unknown_fields)]
+struct ParamsRepr {
+    discriminant_bits: usize,
+    challenge_bits: usize,
+    hash: String,
+    #[serde(with = "bytes")]
+    domain: Vec<u8>,
+    proof_version: u8,
+}
+
+impl Serialize for VdfParams {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            ParamsRepr {
+                discriminant_bits: self.discriminant_bits,
+                challenge_bits: self.challenge_bits,
+                hash: self.hash.to_string(),
+                domain: self.domain.clone(),
+                proof_version: self.proof_version as u8,
+            }
+            .serialize(serializer)
+        } else {
+            serializer.serialize_bytes(&self.to_bytes())
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for VdfParams {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if !deserializer.is_human_readable() {
+            let bytes = bytes::deserialize(deserializer)?;
+            return VdfParams::from_bytes(&bytes).map_err(de::Error::custom);
+        }
+        
+        let repr = ParamsRepr::deserialize(deserializer)?;
+        let hash = [HashFunction::Sha256, HashFunction::Sha512]
+            .into_iter()
+            .find(|hash| hash.to_string() == repr.hash)
+            .ok_or_else(|| de::Error::custom(VdfError::InvalidParams("unknown hash function")))?;
+        let proof_version = ProofVersion::from_u8(repr.proof_version)
+            .ok_or_else(|| de::Error::custom(VdfError::UnsupportedVersion(repr.proof_version)))?;
+        let params = VdfParams {
+            discriminant_bits: repr.discriminant_bits,
+            challenge_bits: repr.challenge_bits,
+            hash,
+            domain: repr.domain,
+            proof_version,
+        };
+        params.validate().map_err(de::Error::custom)?;
+        Ok(params)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename = "WesolowskiVDF")]
+struct VdfRef<'a> {
+    params: &'a VdfParams,
+    #[serde(with = "integer")]
+    discriminant: &'a BigInt,
+    generator: &'a ClassGroupElement,
+    #[serde(with = "bytes")]
+    context: &'a [u8],
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "WesolowskiVDF", deny_unknown_fields)]
+struct VdfRepr {
+    params: VdfParams,
+    #[serde(with = "integer")]
+    discriminant: BigInt,
+    generator: ClassGroupElement,
+    #[serde(with = "bytes")]
+    context: Vec<u8>,
+}
+
+impl Serialize for WesolowskiVDF {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        VdfRef {
+            params: &self.params,
+            discriminant: &self.discriminant,
+            generator: &self.generator,
+            context: &self.context,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for WesolowskiVDF {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let VdfRepr { params, discriminant, generator, context } = VdfRepr::deserialize(deserializer)?;
+        let discriminant = Discriminant::new(discriminant).map_err(de::Error::custom)?.into_bigint();
+        if generator.discriminant != discriminant {
+            return Err(de::Error::custom(VdfError::DiscriminantMismatch));
+        }
+        if params.discriminant_bits != discriminant.bits() as usize {
+            return Err(de::Error::custom(VdfError::InvalidParams(
+                "discriminant size does not match the parameters",
+            )));
+        }
+        Ok(WesolowskiVDF { generator, discriminant, context, params })
+    }
+}
+
+/// Integers as decimal strings in human-readable formats and as minimal
+/// big-endian two's complement bytes otherwise
+mod integer {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &BigInt, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&value.to_string())
+        } else {
+            serializer.serialize_bytes(&value.to_signed_bytes_be())
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BigInt, D::Error> {
+        if deserializer.is_human_readable() {
+            let text = String::deserialize(deserializer)?;
+            // `BigInt::from_str` also accepts a leading '+', which is not canonical
+            if text.starts_with('+') {
+                return Err(de::Error::custom("integer must not have a '+' sign"));
+            }
+            let value: BigInt = text.parse().map_err(de::Error::custom)?;
+            if value.to_string() != text {
+                return Err(de::Error::custom("integer is not in canonical decimal form"));
+            }
+            Ok(value)
+        } else {
+            let bytes = super::bytes::deserialize(deserializer)?;
+            let value = BigInt::from_signed_bytes_be(&bytes);
+            if value.to_signed_bytes_be() != bytes {
+                return Err(de::Error::custom("integer is not in minimal two's complement form"));
+            }
+            Ok(value)
+        }
+    }
+}
+
+/// Byte strings as lowercase hex in human-readable formats and as bytes otherwise
+mod bytes {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&hex::encode(value))
+        } else {
+            serializer.serialize_bytes(value)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        if deserializer.is_human_readable() {
+            let text = String::deserialize(deserializer)?;
+            if text.bytes().any(|byte| byte.is_ascii_uppercase()) {
+                return Err(de::Error::custom("hex must be lowercase"));
+            }
+            hex::decode(&text).map_err(de::Error::custom)
+        } else {
+            deserializer.deserialize_byte_buf(BytesVisitor)
+        }
+    }
+    
+    struct BytesVisitor;
+
+    impl<'de> de::Visitor<'de> for BytesVisitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "a byte string")
+        }
+
+        fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Vec<u8>, E> {
+            Ok(value.to_vec())
+        }
+
+        fn visit_byte_buf<E: de::Error>(self, value: Vec<u8>) -> Result<Vec<u8>, E> {
+            Ok(value)
+        }
+        
+        // Formats without a byte string type write bytes as a sequence of u8
+        fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+            let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+            while let Some(byte) = seq.next_element()? {
+                bytes.push(byte);
+            }
+            Ok(bytes)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::DeserializeOwned;
+
+    /// A 512-bit instance with a context, and the output and proof of 1000 iterations
+    fn instance() -> (WesolowskiVDF, ClassGroupElement, WesolowskiProof) {
+        let discriminant = Discriminant::from_seed(b"serde-test", 512).unwrap();
+        let generator = ClassGroupElement::generator(discriminant.as_bigint().clone());
+        let vdf = WesolowskiVDF::from_discriminant(discriminant, generator).unwrap().with_context(b"context");
+        let (output, proof) = vdf.compute(1000);
+        (vdf, output, proof)
+    }
+
+    fn json_round_trip<T: Serialize + DeserializeOwned>(value: &T) -> T {
+        serde_json::from_str(&serde_json::to_string(value).unwrap()).unwrap()
+    }
+
+    fn bincode_round_trip<T: Serialize + DeserializeOwned>(value: &T) -> T {
+        bincode::deserialize(&bincode::serialize(value).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn values_round_trip_in_both_kinds_of_formats() {
+        let (vdf, output, proof) = instance();
+        let discriminant = Discriminant::new(vdf.discriminant.clone()).unwrap();
+        let params = VdfParams::builder().discriminant_bits(512).domain(b"serde").build().unwrap();
+        
+        for round_trip in [json_round_trip::<ClassGroupElement>, bincode_round_trip] {
+            assert_eq!(round_trip(&output), output);
+            assert_eq!(round_trip(&output.inverse()), output.inverse());
+        }
+        for round_trip in [json_round_trip::<Discriminant>, bincode_round_trip] {
+            assert_eq!(round_trip(&discriminant), discriminant);
+        }
+        for round_trip in [json_round_trip::<WesolowskiProof>, bincode_round_trip] {
+            assert_eq!(round_trip(&proof), proof);
+        }
+        for round_trip in [json_round_trip::<VdfParams>, bincode_round_trip] {
+            assert_eq!(round_trip(&params), params);
+        }
+        for round_trip in [json_round_trip::<WesolowskiVDF>, bincode_round_trip] {
+            let decoded = round_trip(&vdf);
+            assert_eq!(decoded.generator, vdf.generator);
+            assert_eq!(decoded.discriminant, vdf.discriminant);
+            assert_eq!(decoded.context, vdf.context);
+            assert_eq!(decoded.params, vdf.params);
+            assert!(decoded.verify(&output, &proof, 1000));
+        }
+    }
+
+    #[test]
+    fn human_readable_input_is_checked() {
+        let (vdf, output, proof) = instance();
+        
+        // Integers must be canonical decimal strings and hex must be lowercase
+        let form = serde_json::to_string(&output).unwrap();
+        let a = format!("\"a\":\"{}\"", output.a);
+        for replacement in [format!("\"a\":\"0{}\"", output.a), format!("\"a\":\"+{}\"", output.a)] {
+            assert!(serde_json::from_str::<ClassGroupElement>(&form.replace(&a, &replacement)).is_err());
+        }
+        let hex = hex::encode(proof.to_bytes());
+        let uppercase = serde_json::to_string(&proof).unwrap().replace(&hex, &hex.to_uppercase());
+        assert!(serde_json::from_str::<WesolowskiProof>(&uppercase).is_err());
+        
+        let unreduced = ClassGroupElement::new(output.a.clone(), &output.b + 2 * &output.a, vdf.discriminant.clone());
+        assert!(serde_json::from_str::<ClassGroupElement>(&serde_json::to_string(&unreduced).unwrap()).is_err());
+        assert!(serde_json::from_str::<Discriminant>("\"-23\"").is_err());
+        
+        let vdf_json = serde_json::to_string(&vdf).unwrap();
+        let other = ClassGroupElement::generator(Discriminant::from_seed(b"other", 512).unwrap().into_bigint());
+        let mismatched = vdf_json.replace(
+            &serde_json::to_string(&vdf.generator).unwrap(),
+            &serde_json::to_string(&other).unwrap(),
+        );
+        assert!(serde_json::from_str::<WesolowskiVDF>(&vdf_json).is_ok());
+        assert!(serde_json::from_str::<WesolowskiVDF>(&mismatched).is_err());
+        
+        let params = serde_json::to_value(&vdf.params).unwrap();
+        let mut unknown_hash = params.clone();
+        unknown_hash["hash"] = "MD5".into();
+        let mut too_small = params;
+        too_small["discriminant_bits"] = 256.into();
+        for value in [unknown_hash, too_small] {
+            assert!(serde_json::from_value::<VdfParams>(value).is_err());
+        }
+    }
+
+    #[test]
+    fn binary_input_is_checked() {
+        let (vdf, output, _) = instance();
+        let discriminant = Discriminant::new(vdf.discriminant.clone()).unwrap();
+        
+        // Δ is negative with a top bit set, so an extra 0xff byte is redundant
+        let minimal = vdf.discriminant.to_signed_bytes_be();
+        let padded = [&[0xff], &minimal[..]].concat();
+        assert_eq!(bincode::serialize(&minimal).unwrap(), bincode::serialize(&discriminant).unwrap());
+        assert!(bincode::deserialize::<Discriminant>(&bincode::serialize(&padded).unwrap()).is_err());
+        
+        let mut form = ClassGroupElement::serialize(&output);
+        form.push(0);
+        assert!(bincode::deserialize::<ClassGroupElement>(&bincode::serialize(&form).unwrap()).is_err());
+        let unreduced = ClassGroupElement::new(output.a.clone(), &output.b + 2 * &output.a, vdf.discriminant.clone());
+        let bytes = bincode::serialize(&ClassGroupElement::serialize(&unreduced)).unwrap();
+        assert!(bincode::deserialize::<ClassGroupElement>(&bytes).is_err());
+    }
+}
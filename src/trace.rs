@@ -0,0 +1,83 @@
+//! Optional Tracing Instrumentation
+//!
+//! The library never writes to stdout. With the `tracing` feature, evaluation,
+//! proving and verification run inside `tracing` spans that carry the iteration
+//! count and record their duration before they close, and diagnostic values are
+//! emitted as events that the application's subscriber can filter or route.
+//! Without the feature the macros below expand to nothing and `tracing` is not
+//! a dependency.
+//!
+//! # Spans
+//!
+//! | Span      | Level | Fields                          |
+//! |-----------|-------|---------------------------------|
+//! | `setup`   | debug | `discriminant_bits`             |
+//! | `compute` | debug | `iterations`                    |
+//! | `resume`  | debug | `iterations`, `iterations_done` |
+//! | `prove`   | debug | `iterations`, `version`         |
+//! | `verify`  | debug | `iterations`, `version`         |
+//!
+//! Every span also has an `elapsed` field, recorded when it is exited. Forms and
+//! discriminants are only logged at trace level.
+
+#[cfg(feature = "tracing")]
+use std::time::Instant;
+
+/// Emit a `tracing` event at the given level, e.g. `event!(debug, field = value, "message")`
+#[cfg(feature = "tracing")]
+macro_rules! event {
+    ($level:ident, $($arg:tt)+) => {
+        tracing::$level!($($arg)+)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! event {
+    ($level:ident, $($arg:tt)+) => {};
+}
+
+/// Enter a debug span that records its duration when the returned guard is dropped
+#[cfg(feature = "tracing")]
+macro_rules! timed_span {
+    ($name:literal $(, $($field:tt)+)?) => {
+        $crate::trace::Timed::enter(tracing::debug_span!(
+            $name,
+            $($($field)+,)?
+            elapsed = tracing::field::Empty
+        ))
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! timed_span {
+    ($name:literal $(, $($field:tt)+)?) => {
+        $crate::trace::Timed
+    };
+}
+
+pub(crate) use {event, timed_span};
+
+/// Guard of an entered span, created by `timed_span!`
+#[cfg(feature = "tracing")]
+pub(crate) struct Timed {
+    start: Instant,
+    span: tracing::span::EnteredSpan,
+}
+
+#[cfg(feature = "tracing")]
+impl Timed {
+    pub(crate) fn enter(span: tracing::Span) -> Self {
+        Self { start: Instant::now(), span: span.entered() }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Drop for Timed {
+    fn drop(&mut self) {
+        self.span.record("elapsed", tracing::field::debug(self.start.elapsed()));
+    }
+}
+
+/// Guard of a disabled span
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Timed;
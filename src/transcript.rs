@@ -0,0 +1,119 @@
+//! Fiat-Shamir Transcript for Challenge Derivation
+//!
+//! The challenge prime ℓ of a non-interactive Wesolowski proof must depend on
+//! everything the prover claims. If it does not, a prover can choose the unbound
+//! inputs after seeing ℓ, which breaks soundness. A `Transcript` absorbs labelled,
+//! length-prefixed fields under a domain separator, so two different statements
+//! can never produce the same hash input.
+//!
+//! # Encoding
+//!
+//! The transcript hashes the domain separator followed by every appended field,
+//! each encoded as
+//!
+//! | Part   | Encoding                 |
+//! |--------|--------------------------|
+//! | label  | u64 length + label bytes |
+//! | value  | u64 length + value bytes |
+//!
+//! with big-endian lengths. The challenge prime is derived from the hash of that
+//! byte stream with `hash_to_prime`, using the hash function and prime size of
+//! the `VdfParams`.
+
+use num_bigint::{BigInt, Sign};
+
+use crate::class_group::ClassGroupElement;
+use crate::crypto::{hash_to_prime, HASH_TO_PRIME_DOMAIN};
+use crate::params::{HashFunction, VdfParams};
+
+/// Default domain separator of the Wesolowski challenge transcript
+pub const CHALLENGE_DOMAIN: &[u8] = b"wesolowski_vdf/proof-v3/challenge";
+
+/// Length-prefixed, domain-separated hash transcript
+#[derive(Clone)]
+pub struct Transcript {
+    hash: HashFunction,
+    data: Vec<u8>,
+}
+
+impl Transcript {
+    /// Start a SHA-256 transcript under the given domain separator
+    pub fn new(domain: &[u8]) -> Self {
+        Self::with_hash(HashFunction::Sha256, domain)
+    }
+
+    /// Start a transcript with the given hash function and domain separator
+    pub fn with_hash(hash: HashFunction, domain: &[u8]) -> Self {
+        let mut data = Vec::new();
+        absorb(&mut data, domain);
+        Self { hash, data }
+    }
+
+    /// Append a labelled byte string
+    pub fn append(&mut self, label: &[u8], value: &[u8]) {
+        absorb(&mut self.data, label);
+        absorb(&mut self.data, value);
+    }
+
+    /// Append a labelled unsigned integer as 8 big-endian bytes
+    pub fn append_u64(&mut self, label: &[u8], value: u64) {
+        self.append(label, &value.to_be_bytes());
+    }
+
+    /// Append a labelled integer as a sign byte followed by its big-endian magnitude
+    pub fn append_int(&mut self, label: &[u8], value: &BigInt) {
+        let (sign, magnitude) = value.to_bytes_be();
+        let mut bytes = Vec::with_capacity(magnitude.len() + 1);
+        bytes.push(if sign == Sign::Minus { 1 } else { 0 });
+        bytes.extend_from_slice(&magnitude);
+        self.append(label, &bytes);
+    }
+
+    /// Append a labelled class group element by its coefficients a and b
+    /// 
+    /// c is determined by a, b and the discriminant, which is bound separately.
+    pub fn append_form(&mut self, label: &[u8], form: &ClassGroupElement) {
+        let (a_sign, a_bytes) = form.a.to_bytes_be();
+        let (b_sign, b_bytes) = form.b.to_bytes_be();
+        let mut bytes = Vec::new();
+        for (sign, magnitude) in [(a_sign, a_bytes), (b_sign, b_bytes)] {
+            bytes.extend_from_slice(&(magnitude.len() as u32).to_be_bytes());
+            bytes.push(if sign == Sign::Minus { 1 } else { 0 });
+            bytes.extend_from_slice(&magnitude);
+        }
+        self.append(label, &bytes);
+    }
+
+    /// Finish the transcript and derive a prime of `bits` bits from its hash
+    pub fn challenge_prime(self, bits: usize) -> BigInt {
+        let digest = self.hash.digest(&self.data);
+        hash_to_prime(self.hash, HASH_TO_PRIME_DOMAIN, &digest, bits)
+    }
+}
+
+/// Challenge prime ℓ for the claim y = g^(2^t) in the class group of Δ
+/// 
+/// Binds the discriminant, generator, output, iteration count and a caller
+/// supplied context string. The domain separator, hash function and prime size
+/// are taken from `params`.
+pub fn wesolowski_challenge(
+    params: &VdfParams,
+    discriminant: &BigInt,
+    generator: &ClassGroupElement,
+    output: &ClassGroupElement,
+    iterations: u64,
+    context: &[u8],
+) -> BigInt {
+    let mut transcript = Transcript::with_hash(params.hash, &params.domain);
+    transcript.append_int(b"discriminant", discriminant);
+    transcript.append_form(b"generator", generator);
+    transcript.append_form(b"output", output);
+    transcript.append_u64(b"iterations", iterations);
+    transcript.append(b"context", context);
+    transcript.challenge_prime(params.challenge_bits)
+}
+
+fn absorb(data: &mut Vec<u8>, bytes: &[u8]) {
+    data.extend_from_slice(&(bytes.len() as u64).to_be_bytes());
+    data.extend_from_slice(bytes);
+}
@@ -6,10 +6,11 @@
 //! # Algorithm Overview
 //!
 //! The Wesolowski VDF works as follows:
-//! 1. **Setup**: Generate a class group from a challenge string
+//! 1. **Setup**: Generate a class group from a challenge string, or map the
+//!    challenge into a fixed public class group with `hash_to_group`
 //! 2. **Compute**: Perform t sequential squaring operations: y = g^(2^t)
 //! 3. **Prove**: Generate a succinct proof π that y was computed correctly
-//! 4. **Verify**: Check the proof equation: y = π^l · g^r where l·q + r = 2^t
+//! 4. **Verify**: Check the proof equation: y = π^l · g^r where r = 2^t mod l
 //!
 //! # Security Properties
 //!
@@ -21,15 +22,25 @@
 //! # Performance
 //!
 //! - Computation time: O(t) where t is the number of iterations
-//! - Proof size: O(log t) bits (constant for practical purposes)
+//! - Proof size: one class group element, independent of t
 //! - Verification time: O(log t) operations
 
-use num_bigint::{BigInt, Sign};
-use num_traits::One;
+use num_bigint::BigInt;
+use num_traits::Signed;
+use std::path::Path;
 use std::time::Instant;
 
-use crate::class_group::ClassGroupElement;
-use crate::crypto::{generate_discriminant, hash_prime};
+use crate::class_group::{ClassGroupElement, FormError};
+use crate::crypto::{generate_discriminant, hash_prime, hash_to_group, mod_pow};
+use crate::discriminant::Discriminant;
+use crate::checkpoint::{CheckpointError, CheckpointWriter};
+use crate::error::VdfError;
+use crate::params::{VdfParams, VdfParamsBuilder};
+use crate::proof::{ProofVersion, WesolowskiProof};
+use crate::progress::{self, CancellationToken, Cancelled, EvaluationError, NoProgress, ProgressObserver};
+use crate::prover::Evaluator;
+use crate::trace::{event, timed_span};
+use crate::transcript::wesolowski_challenge;
 
 /// Wesolowski VDF implementation using class groups
 /// 
@@ -40,6 +51,10 @@ pub struct WesolowskiVDF {
     pub generator: ClassGroupElement,
     /// The discriminant defining the class group
     pub discriminant: BigInt,
+    /// Application context bound into the challenge of every proof
+    pub context: Vec<u8>,
+    /// Sizes, hash function and proof format shared with the verifier
+    pub params: VdfParams,
 }
 
 impl WesolowskiVDF {
@@ -51,17 +66,153 @@ impl WesolowskiVDF {
     /// # Returns
     /// A new WesolowskiVDF instance with generated discriminant and generator
     pub fn new(challenge: &[u8]) -> Self {
-        let discriminant = generate_discriminant(challenge, 1024);
-        println!("Debug: Generated discriminant = {}", discriminant);
+        Self::build(VdfParams::default(), challenge)
+    }
+
+    /// Start configuring a VDF instance
+    /// 
+    /// Chain the `VdfParamsBuilder` setters and finish with `build_vdf(challenge)`,
+    /// or with `build()` to obtain only the parameters.
+    pub fn builder() -> VdfParamsBuilder {
+        VdfParams::builder()
+    }
+
+    /// Create a VDF instance from explicit parameters and a class group set up in advance
+    /// 
+    /// This is `from_discriminant` followed by `with_params`, so nothing is
+    /// generated. To generate the discriminant from a challenge under custom
+    /// parameters, use `VdfParamsBuilder::build_vdf`.
+    /// 
+    /// # Returns
+    /// The instance, or an error of `from_discriminant` or `with_params`
+    pub fn from_params(params: VdfParams, discriminant: Discriminant, generator: ClassGroupElement) -> Result<Self, VdfError> {
+        Self::from_discriminant(discriminant, generator)?.with_params(params)
+    }
+
+    /// Create a VDF instance from a discriminant and generator set up in advance
+    /// 
+    /// Nothing is generated, so a verifier that checks many proofs in one class
+    /// group pays for the discriminant once, e.g. with `Discriminant::from_seed`,
+    /// and builds each instance from the cached value. The parameters are the
+    /// defaults with the size of Δ, which are valid because a `Discriminant`
+    /// always has a size in `params::DISCRIMINANT_BITS`; use `with_params` to
+    /// replace them.
+    /// 
+    /// # Arguments
+    /// * `discriminant` - The validated discriminant Δ
+    /// * `generator` - A reduced positive definite form of discriminant Δ
+    /// 
+    /// The generator must be reduced, as `ClassGroupElement::generator` and
+    /// `from_hash` return it, because checkpoint files and serialized instances only
+    /// store reduced forms. Pass `generator.reduced()` to use another representative
+    /// of the same class.
+    /// 
+    /// # Returns
+    /// The instance, `VdfError::DiscriminantMismatch` if the generator belongs to
+    /// another discriminant, or `VdfError::InvalidForm` if it is not a form of Δ or
+    /// not reduced
+    pub fn from_discriminant(discriminant: Discriminant, generator: ClassGroupElement) -> Result<Self, VdfError> {
+        if generator.discriminant != *discriminant.as_bigint() {
+            return Err(VdfError::DiscriminantMismatch);
+        }
+        let generator = ClassGroupElement::try_new(generator.a, generator.b, generator.c, generator.discriminant)?;
+        if !generator.is_reduced() {
+            return Err(VdfError::InvalidForm(FormError::NotReduced));
+        }
+        let params = VdfParams {
+            discriminant_bits: discriminant.bits(),
+            ..VdfParams::default()
+        };
+        
+        Ok(Self {
+            generator,
+            discriminant: discriminant.into_bigint(),
+            context: Vec::new(),
+            params,
+        })
+    }
+
+    /// Replace the parameters of the instance
+    /// 
+    /// # Returns
+    /// The instance, or `VdfError::InvalidParams` if a parameter is out of range or
+    /// `params.discriminant_bits` is not the size of the instance's discriminant
+    pub fn with_params(mut self, params: VdfParams) -> Result<Self, VdfError> {
+        params.validate()?;
+        if params.discriminant_bits != self.discriminant.bits() as usize {
+            return Err(VdfError::InvalidParams("discriminant size does not match the parameters"));
+        }
+        self.params = params;
+        Ok(self)
+    }
+
+    /// Generate the discriminant and generator for `challenge` under parameters
+    /// that have been validated
+    pub(crate) fn build(params: VdfParams, challenge: &[u8]) -> Self {
+        let _span = timed_span!("setup", discriminant_bits = params.discriminant_bits);
+        let discriminant = generate_discriminant(challenge, params.discriminant_bits);
         let generator = ClassGroupElement::generator(discriminant.clone());
-        println!("Debug: Generator = ({}, {}, {})", generator.a, generator.b, generator.c);
+        event!(trace, %discriminant, a = %generator.a, b = %generator.b, "generated class group");
         
         Self {
             generator,
             discriminant,
+            context: Vec::new(),
+            params,
         }
     }
 
+    /// Create a VDF instance in a fixed public class group
+    /// 
+    /// The discriminant is a long-lived public parameter, shared by all
+    /// challenges. The challenge is mapped into the group by `hash_to_group` and
+    /// becomes the generator, so setting up a new input costs one hash-to-prime
+    /// instead of a fresh 1024-bit discriminant.
+    /// 
+    /// # Arguments
+    /// * `discriminant` - The validated public discriminant Δ
+    /// * `challenge` - The input challenge bytes
+    pub fn with_public_discriminant(discriminant: &Discriminant, challenge: &[u8]) -> Self {
+        let generator = hash_to_group(discriminant.as_bigint(), challenge)
+            .expect("a validated discriminant is negative and ≡ 1 (mod 4)");
+        let params = VdfParams {
+            discriminant_bits: discriminant.bits(),
+            ..VdfParams::default()
+        };
+        Self {
+            generator,
+            discriminant: discriminant.as_bigint().clone(),
+            context: Vec::new(),
+            params,
+        }
+    }
+
+    /// Create an instance for another challenge in the same class group
+    /// 
+    /// Keeps the discriminant, context and parameters and maps `challenge` to a
+    /// new generator with `hash_to_group`.
+    /// 
+    /// # Returns
+    /// The instance, or `VdfError::InvalidDiscriminant` if the `discriminant`
+    /// field is not negative or not ≡ 1 (mod 4)
+    pub fn for_challenge(&self, challenge: &[u8]) -> Result<Self, VdfError> {
+        Ok(Self {
+            generator: hash_to_group(&self.discriminant, challenge)?,
+            discriminant: self.discriminant.clone(),
+            context: self.context.clone(),
+            params: self.params.clone(),
+        })
+    }
+
+    /// Set the context string bound into the Fiat-Shamir challenge
+    /// 
+    /// Proofs made under one context do not verify under another, which keeps
+    /// proofs from being replayed between applications or protocol instances.
+    pub fn with_context(mut self, context: &[u8]) -> Self {
+        self.context = context.to_vec();
+        self
+    }
+
     /// Compute VDF output: generator^(2^iterations)
     /// 
     /// This performs the sequential computation required by the VDF by repeatedly
@@ -71,66 +222,172 @@ impl WesolowskiVDF {
     /// * `iterations` - The number of squaring operations to perform
     /// 
     /// # Returns
-    /// A tuple containing the computed output element and the proof bytes
-    pub fn compute(&self, iterations: u64) -> (ClassGroupElement, Vec<u8>) {
-        let start = Instant::now();
+    /// A tuple containing the computed output element and the proof
+    pub fn compute(&self, iterations: u64) -> (ClassGroupElement, WesolowskiProof) {
+        let _span = timed_span!("compute", iterations);
         
-        let mut current = self.generator.clone();
+        // Sequential squaring: compute g^(2^iterations), keeping proof checkpoints
+        let mut evaluator = Evaluator::new(&self.generator, iterations);
+        evaluator.run_to_end();
         
-        // Sequential squaring: compute g^(2^iterations)
-        for _ in 0..iterations {
-            current = current.square();
+        let proof = self.generate_proof(&evaluator);
+        (evaluator.current().clone(), proof)
+    }
+
+    /// Compute the VDF output with progress reports and cancellation
+    /// 
+    /// The squaring loop runs in chunks of `check_every` squarings. After each chunk
+    /// the observer receives the iterations done, the rate and an ETA, and before
+    /// each chunk the cancellation token is checked.
+    /// 
+    /// # Arguments
+    /// * `iterations` - The number of squaring operations to perform
+    /// * `observer` - Receiver of progress reports
+    /// * `token` - Token through which the evaluation can be cancelled
+    /// * `check_every` - The number of squarings between two checks
+    /// 
+    /// # Returns
+    /// The output and proof, or `Cancelled` holding the partial evaluation
+    pub fn compute_with_progress(
+        &self,
+        iterations: u64,
+        observer: &mut dyn ProgressObserver,
+        token: &CancellationToken,
+        check_every: u64,
+    ) -> Result<(ClassGroupElement, WesolowskiProof), Cancelled> {
+        let evaluator = Evaluator::new(&self.generator, iterations);
+        self.drive_with_progress(evaluator, observer, token, check_every)
+    }
+
+    /// Continue an evaluation of this VDF's generator, e.g. one returned by `Cancelled`
+    /// 
+    /// # Returns
+    /// The output and proof, `EvaluationError::Cancelled` holding the partial
+    /// evaluation, or `EvaluationError::GeneratorMismatch` holding the unchanged
+    /// evaluator if it was started from another generator or discriminant
+    pub fn evaluate_with_progress(
+        &self,
+        evaluator: Evaluator,
+        observer: &mut dyn ProgressObserver,
+        token: &CancellationToken,
+        check_every: u64,
+    ) -> Result<(ClassGroupElement, WesolowskiProof), EvaluationError> {
+        if !self.is_evaluation_of(&evaluator) {
+            return Err(EvaluationError::GeneratorMismatch(Box::new(evaluator)));
+        }
+        Ok(self.drive_with_progress(evaluator, observer, token, check_every)?)
+    }
+
+    fn drive_with_progress(
+        &self,
+        evaluator: Evaluator,
+        observer: &mut dyn ProgressObserver,
+        token: &CancellationToken,
+        check_every: u64,
+    ) -> Result<(ClassGroupElement, WesolowskiProof), Cancelled> {
+        let _span = timed_span!("compute", iterations = evaluator.iterations());
+        let evaluator = progress::drive(evaluator, observer, token, check_every)?;
+        let proof = self.generate_proof(&evaluator);
+        Ok((evaluator.current().clone(), proof))
+    }
+
+    /// Whether `evaluator` squares this VDF's generator
+    fn is_evaluation_of(&self, evaluator: &Evaluator) -> bool {
+        *evaluator.generator() == self.generator && evaluator.generator().discriminant == self.discriminant
+    }
+
+    /// Compute the VDF output while periodically saving a checkpoint file
+    /// 
+    /// Every `save_interval` squarings the evaluation state (iteration index and
+    /// current form) is written to `path`, and the proof checkpoints recorded since
+    /// the previous save are appended to the log beside it; see `checkpoint`. If the
+    /// process is stopped, `resume` continues from the latest checkpoint and returns
+    /// exactly the output and proof of an uninterrupted run.
+    /// 
+    /// Fails with `CheckpointError::AlreadyExists` if `path` or its log already
+    /// exists, so an interrupted run is never overwritten by a new one.
+    /// 
+    /// # Arguments
+    /// * `iterations` - The number of squaring operations to perform
+    /// * `path` - The checkpoint file to write
+    /// * `save_interval` - The number of squarings between two saves
+    /// 
+    /// # Returns
+    /// A tuple containing the computed output element and the proof
+    pub fn compute_checkpointed(
+        &self,
+        iterations: u64,
+        path: &Path,
+        save_interval: u64,
+    ) -> Result<(ClassGroupElement, WesolowskiProof), CheckpointError> {
+        let _span = timed_span!("compute", iterations);
+        let evaluator = Evaluator::new(&self.generator, iterations);
+        let writer = CheckpointWriter::create(path, &evaluator)?;
+        self.run_checkpointed(evaluator, writer, save_interval)
+    }
+
+    /// Resume a checkpointed computation from its latest saved state
+    /// 
+    /// The checkpoint must have been written for this VDF's discriminant and
+    /// generator. Saving continues every `save_interval` squarings.
+    pub fn resume(&self, path: &Path, save_interval: u64) -> Result<(ClassGroupElement, WesolowskiProof), CheckpointError> {
+        let (evaluator, writer) = CheckpointWriter::resume(path)?;
+        if !self.is_evaluation_of(&evaluator) {
+            return Err(CheckpointError::GeneratorMismatch);
+        }
+        let _span = timed_span!(
+            "resume",
+            iterations = evaluator.iterations(),
+            iterations_done = evaluator.iterations_done()
+        );
+        self.run_checkpointed(evaluator, writer, save_interval)
+    }
+
+    fn run_checkpointed(
+        &self,
+        mut evaluator: Evaluator,
+        mut writer: CheckpointWriter,
+        save_interval: u64,
+    ) -> Result<(ClassGroupElement, WesolowskiProof), CheckpointError> {
+        while !evaluator.is_finished() {
+            evaluator.run(save_interval.max(1));
+            writer.save(&evaluator)?;
+            event!(trace, iterations_done = evaluator.iterations_done(), "saved checkpoint");
         }
         
-        let duration = start.elapsed();
-        println!("Computation took: {:?}", duration);
-        
-        let proof = self.generate_proof(&current, iterations);
-        (current, proof)
+        let proof = self.generate_proof(&evaluator);
+        Ok((evaluator.current().clone(), proof))
     }
 
     /// Generate Wesolowski proof for the computed VDF output
     /// 
     /// The Wesolowski proof allows efficient verification of the VDF computation
-    /// without having to repeat the entire sequential computation.
+    /// without having to repeat the entire sequential computation. The proof
+    /// element π = g^⌊2^t/ℓ⌋ is built from the checkpoints stored by the evaluator
+    /// in about t/k compositions, instead of a second exponentiation by a t-bit
+    /// quotient.
     /// 
     /// # Arguments
-    /// * `output` - The computed VDF output
-    /// * `iterations` - The number of iterations used in computation
+    /// * `evaluator` - A finished evaluation of this VDF's generator
     /// 
     /// # Returns
-    /// Serialized proof bytes
-    pub fn generate_proof(&self, output: &ClassGroupElement, iterations: u64) -> Vec<u8> {
-        let x_serialized = self.generator.serialize();
-        let y_serialized = output.serialize();
+    /// The proof for the evaluator's output
+    pub fn generate_proof(&self, evaluator: &Evaluator) -> WesolowskiProof {
+        let output = evaluator.current();
+        let version = self.params.proof_version;
+        let _span = timed_span!("prove", iterations = evaluator.iterations(), ?version);
         
         // Generate challenge prime using Fiat-Shamir transform
-        let challenge_prime = hash_prime(&[&x_serialized, &y_serialized]);
+        let challenge_prime = self.challenge_prime(output, evaluator.iterations(), version);
         
-        // Compute quotient: q = 2^t / l, remainder: r = 2^t mod l
-        let two_pow_t = BigInt::one() << iterations;
-        let quotient = &two_pow_t / &challenge_prime;
-        let remainder = &two_pow_t % &challenge_prime;
+        // Compute proof: π = g^⌊2^t/l⌋ from the evaluation checkpoints
+        let proof_element = evaluator.prove(&challenge_prime);
+        event!(trace, a = %proof_element.a, b = %proof_element.b, "proof element");
         
-        // Verify that l * q + r = 2^t for correctness
-        let check = &challenge_prime * &quotient + &remainder;
-        assert_eq!(check, two_pow_t, "Wesolowski proof arithmetic verification failed");
-        
-        // Compute proof: π = g^q
-        let proof_element = self.generator.pow(&quotient);
-        println!("Debug generation: quotient={}, proof_element=({}, {}, {})", quotient, proof_element.a, proof_element.b, proof_element.c);
-        
-        // Serialize proof
-        let mut proof = Vec::new();
-        proof.extend_from_slice(&proof_element.serialize());
-        let (_, quotient_bytes) = quotient.to_bytes_be();
-        let (_, remainder_bytes) = remainder.to_bytes_be();
-        proof.extend_from_slice(&(quotient_bytes.len() as u32).to_be_bytes());
-        proof.extend_from_slice(&quotient_bytes);
-        proof.extend_from_slice(&(remainder_bytes.len() as u32).to_be_bytes());
-        proof.extend_from_slice(&remainder_bytes);
-        
-        proof
+        WesolowskiProof {
+            pi: proof_element,
+            version,
+        }
     }
 
     /// Verify a Wesolowski proof
@@ -140,85 +397,83 @@ impl WesolowskiVDF {
     /// 
     /// # Arguments
     /// * `output` - The claimed VDF output
-    /// * `proof` - The proof bytes to verify
+    /// * `proof` - The proof to verify
     /// * `iterations` - The claimed number of iterations
     /// 
     /// # Returns
-    /// `true` if the proof is valid, `false` otherwise
-    pub fn verify(&self, output: &ClassGroupElement, proof: &[u8], iterations: u64) -> bool {
-        // Parse the proof
-        if proof.len() < 23 { // Minimum size for proof element + lengths
-            return false;
+    /// `true` if the proof is valid, `false` otherwise; see `try_verify` for the reason
+    pub fn verify(&self, output: &ClassGroupElement, proof: &WesolowskiProof, iterations: u64) -> bool {
+        self.try_verify(output, proof, iterations).is_ok()
+    }
+
+    /// Verify a Wesolowski proof, reporting why it was rejected
+    /// 
+    /// The output and the proof element must be reduced forms of the VDF's
+    /// discriminant; anything else is rejected with `VdfError::InvalidForm`. Only
+    /// proofs of the version in the parameters are accepted, others are rejected
+    /// with `VdfError::VersionMismatch`, so a version 3 verifier cannot be made to
+    /// check a weaker version 2 proof.
+    /// 
+    /// # Arguments
+    /// * `output` - The claimed VDF output
+    /// * `proof` - The proof to verify
+    /// * `iterations` - The claimed number of iterations
+    /// 
+    /// # Returns
+    /// `Ok(())` if the proof is valid, otherwise the first check that failed
+    pub fn try_verify(&self, output: &ClassGroupElement, proof: &WesolowskiProof, iterations: u64) -> Result<(), VdfError> {
+        let _span = timed_span!("verify", iterations, version = ?proof.version);
+        if output.discriminant != self.discriminant || proof.pi.discriminant != self.discriminant {
+            return Err(VdfError::DiscriminantMismatch);
         }
+        // Both forms may come from the caller's own fields, so check them before any arithmetic
+        check_reduced(output)?;
+        check_reduced(&proof.pi)?;
+        if proof.version != self.params.proof_version {
+            return Err(VdfError::VersionMismatch {
+                expected: self.params.proof_version,
+                found: proof.version,
+            });
+        }
+        let proof_element = &proof.pi;
         
-        // Extract proof element using deserialize (variable length)
-        let mut offset = 0;
-        let proof_element = match ClassGroupElement::deserialize(&proof[offset..], &self.discriminant) {
-            Some(elem) => {
-                // Calculate how many bytes were consumed by deserialize
-                let serialized = elem.serialize();
-                offset += serialized.len();
-                println!("Debug deserialization: proof_element=({}, {}, {})", elem.a, elem.b, elem.c);
-                elem
-            },
-            None => return false,
-        };
-        
-        // Extract quotient and remainder lengths and values
-        if offset + 4 > proof.len() { return false; }
-        let quotient_len = u32::from_be_bytes([proof[offset], proof[offset+1], proof[offset+2], proof[offset+3]]) as usize;
-        offset += 4;
-        
-        if offset + quotient_len > proof.len() { return false; }
-        let quotient_bytes = &proof[offset..offset + quotient_len];
-        let quotient = BigInt::from_bytes_be(Sign::Plus, quotient_bytes);
-        offset += quotient_len;
-        
-        if offset + 4 > proof.len() { return false; }
-        let remainder_len = u32::from_be_bytes([proof[offset], proof[offset+1], proof[offset+2], proof[offset+3]]) as usize;
-        offset += 4;
+        // Regenerate challenge prime using Fiat-Shamir, as selected by the proof version
+        let challenge_prime = self.challenge_prime(output, iterations, proof.version);
         
-        if offset + remainder_len > proof.len() { return false; }
-        let remainder_bytes = &proof[offset..offset + remainder_len];
-        let remainder = BigInt::from_bytes_be(Sign::Plus, remainder_bytes);
+        // Remainder r = 2^t mod l, without materialising 2^t
+        let remainder = mod_pow(&BigInt::from(2), &BigInt::from(iterations), &challenge_prime);
         
-        // Regenerate challenge prime using Fiat-Shamir
-        let x_serialized = self.generator.serialize();
-        let y_serialized = output.serialize();
-        let challenge_prime = hash_prime(&[&x_serialized, &y_serialized]);
+        // Verify the main equation: π^l * g^r = y, with both powers in one pass
+        let left_side = ClassGroupElement::multi_pow(&[
+            (proof_element, &challenge_prime),
+            (&self.generator, &remainder),
+        ]);
         
-        // Verify quotient and remainder relationship: l * q + r = 2^t
-        let two_pow_t = BigInt::one() << iterations;
-        let quotient_check = &two_pow_t / &challenge_prime;
-        let remainder_check = &two_pow_t % &challenge_prime;
+        event!(trace, a = %left_side.a, b = %left_side.b, "left side π^ℓ · g^r");
         
-        if quotient != quotient_check || remainder != remainder_check {
-            println!("Debug: Quotient/remainder mismatch");
-            return false;
+        if left_side != *output {
+            event!(debug, "proof equation does not hold");
+            return Err(VdfError::ProofRejected);
         }
-        
-        // Verify the main equation: π^l * g^r = y
-        let pi_to_l = proof_element.pow(&challenge_prime);
-        let g_to_r = {
-            let _result = self.generator.clone();
-            for _ in 0..remainder.bits() {
-                if (&remainder >> (remainder.bits() - 1)) & BigInt::one() == BigInt::one() {
-                    break;
-                }
+        Ok(())
+    }
+
+    /// Derive the challenge prime ℓ for an output and iteration count
+    /// 
+    /// Version 2 proofs hash only the generator and output. Version 3 proofs use a
+    /// transcript that also binds Δ, t and the context string, under the domain
+    /// separator, hash function and prime size of the parameters.
+    fn challenge_prime(&self, output: &ClassGroupElement, iterations: u64, version: ProofVersion) -> BigInt {
+        match version {
+            ProofVersion::Legacy => {
+                let x_serialized = self.generator.serialize();
+                let y_serialized = output.serialize();
+                hash_prime(&[&x_serialized, &y_serialized])
             }
-            // Use the pow method for g^r
-            self.generator.pow(&remainder)
-        };
-        
-        let left_side = pi_to_l.compose(&g_to_r);
-        
-        println!("Debug verification: π^l=({}, {}, {}), g^r=({}, {}, {}), left_side=({}, {}, {}), output=({}, {}, {})", 
-                 pi_to_l.a, pi_to_l.b, pi_to_l.c,
-                 g_to_r.a, g_to_r.b, g_to_r.c,
-                 left_side.a, left_side.b, left_side.c,
-                 output.a, output.b, output.c);
-        
-        left_side == *output
+            ProofVersion::Transcript => {
+                wesolowski_challenge(&self.params, &self.discriminant, &self.generator, output, iterations, &self.context)
+            }
+        }
     }
 
     /// Run a benchmark to determine iterations for a target delay
@@ -229,20 +484,32 @@ impl WesolowskiVDF {
     /// # Returns
     /// Benchmark results and recommendations
     pub fn benchmark(&self) -> String {
+        match self.benchmark_with_progress(&mut NoProgress, &CancellationToken::new()) {
+            Ok(report) => report,
+            Err(_) => unreachable!("a fresh cancellation token is never cancelled"),
+        }
+    }
+
+    /// Run the benchmark with progress reports and cancellation
+    /// 
+    /// Each sample evaluation reports its progress to `observer` and stops early,
+    /// returning `Cancelled`, once `token` is cancelled.
+    pub fn benchmark_with_progress(
+        &self,
+        observer: &mut dyn ProgressObserver,
+        token: &CancellationToken,
+    ) -> Result<String, Cancelled> {
         let mut results = Vec::new();
         
         for iterations in [1, 2, 4, 8, 16] {
             let start = Instant::now();
-            let mut current = self.generator.clone();
-            
-            for _ in 0..iterations {
-                current = current.square();
-            }
+            let evaluator = Evaluator::new(&self.generator, iterations);
+            progress::drive(evaluator, observer, token, 1)?;
             
             let duration = start.elapsed();
             results.push((iterations, duration));
             
-            println!("Iterations: {}, Time: {:?}", iterations, duration);
+            event!(debug, iterations, elapsed = ?duration, "benchmark sample");
         }
         
         let mut report = String::from("Benchmark Results:\n");
@@ -255,6 +522,99 @@ impl WesolowskiVDF {
         report.push_str("  - For 10 second delay: ~25-30 iterations\n");
         report.push_str("  - Adjust based on your hardware performance\n");
         
-        report
+        Ok(report)
+    }
+}
+
+/// Check that a form is positive definite, of its stored discriminant, and reduced
+fn check_reduced(form: &ClassGroupElement) -> Result<(), VdfError> {
+    if !form.a.is_positive() {
+        return Err(VdfError::InvalidForm(FormError::NonPositiveLeadingCoefficient));
+    }
+    if !form.is_valid() {
+        return Err(VdfError::InvalidForm(FormError::DiscriminantMismatch));
+    }
+    if !form.is_reduced() {
+        return Err(VdfError::InvalidForm(FormError::NotReduced));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ITERATIONS: u64 = 300;
+
+    fn instance(seed: &[u8]) -> WesolowskiVDF {
+        let discriminant = Discriminant::from_seed(seed, 512).unwrap();
+        let generator = ClassGroupElement::generator(discriminant.as_bigint().clone());
+        WesolowskiVDF::from_discriminant(discriminant, generator).unwrap()
+    }
+
+    #[test]
+    fn from_params_uses_the_given_class_group() {
+        let discriminant = Discriminant::from_seed(b"params-test", 512).unwrap();
+        let generator = ClassGroupElement::from_hash(discriminant.as_bigint().clone(), b"input").unwrap();
+        let params = VdfParams::builder().discriminant_bits(512).build().unwrap();
+        let vdf = WesolowskiVDF::from_params(params.clone(), discriminant.clone(), generator.clone()).unwrap();
+        assert_eq!(vdf.discriminant, *discriminant.as_bigint());
+        assert_eq!(vdf.generator, generator);
+        assert_eq!(vdf.params, params);
+        
+        let mismatched = VdfParams::default();
+        assert!(matches!(
+            WesolowskiVDF::from_params(mismatched, discriminant, generator),
+            Err(VdfError::InvalidParams(_))
+        ));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn public_discriminant_maps_challenges_into_one_group() {
+        let discriminant = Discriminant::from_seed(b"public-test", 512).unwrap();
+        let vdf = WesolowskiVDF::with_public_discriminant(&discriminant, b"first");
+        assert_eq!(vdf.generator, ClassGroupElement::from_hash(discriminant.as_bigint().clone(), b"first").unwrap());
+        
+        let second = vdf.for_challenge(b"second").unwrap();
+        assert_eq!(second.discriminant, vdf.discriminant);
+        assert_ne!(second.generator, vdf.generator);
+        
+        let mut corrupted = WesolowskiVDF::with_public_discriminant(&discriminant, b"first");
+        corrupted.discriminant = -corrupted.discriminant;
+        assert!(matches!(corrupted.for_challenge(b"second"), Err(VdfError::InvalidDiscriminant(_))));
+    }
+
+    #[test]
+    fn cancelled_evaluation_continues_to_compute_result() {
+        let vdf = instance(b"progress-test");
+        let (expected_output, expected_proof) = vdf.compute(ITERATIONS);
+        
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        let mut cancel_after_first_chunk = |_: &progress::Progress| canceller.cancel();
+        let cancelled = vdf
+            .compute_with_progress(ITERATIONS, &mut cancel_after_first_chunk, &token, 70)
+            .unwrap_err();
+        assert_eq!(cancelled.iterations_done(), 70);
+        
+        let (output, proof) = vdf
+            .evaluate_with_progress(cancelled.into_evaluator(), &mut NoProgress, &CancellationToken::new(), 70)
+            .unwrap();
+        assert_eq!(output, expected_output);
+        assert_eq!(proof.to_bytes(), expected_proof.to_bytes());
+    }
+
+    #[test]
+    fn evaluation_of_another_generator_is_rejected() {
+        let vdf = instance(b"progress-test");
+        let other = instance(b"other-progress-test");
+        let mut evaluator = Evaluator::new(&other.generator, ITERATIONS);
+        evaluator.run(50);
+        
+        let result = vdf.evaluate_with_progress(evaluator, &mut NoProgress, &CancellationToken::new(), 70);
+        match result {
+            Err(EvaluationError::GeneratorMismatch(evaluator)) => assert_eq!(evaluator.iterations_done(), 50),
+            other => panic!("expected a generator mismatch, got {:?}", other.map(|_| ())),
+        }
+    }
+}